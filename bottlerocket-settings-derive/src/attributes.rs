@@ -0,0 +1,126 @@
+use quote::format_ident;
+use syn::meta::ParseNestedMeta;
+use syn::{parse_quote, DeriveInput, Ident, LitStr, Path, Type};
+
+/// The parsed contents of a `#[settings(...)]` attribute on a settings model.
+#[derive(Default)]
+pub(crate) struct ModelAttributes {
+    pub(crate) version: Option<LitStr>,
+    pub(crate) partial: Option<Type>,
+    pub(crate) partial_derives: Vec<Path>,
+    pub(crate) forward: Option<Type>,
+    pub(crate) backward: Option<Type>,
+    pub(crate) dependent_settings: Option<Type>,
+    pub(crate) migrate_forward: Option<Path>,
+    pub(crate) migrate_backward: Option<Path>,
+    pub(crate) set: Option<Path>,
+    pub(crate) generate: Option<Path>,
+    pub(crate) validate: Option<Path>,
+    pub(crate) skip_validation_on_set: bool,
+    pub(crate) schema: bool,
+    pub(crate) required_settings: Vec<SettingDependency>,
+    pub(crate) validated_settings: Vec<SettingDependency>,
+}
+
+/// A `required_setting(...)` or `validated_setting(...)` declaration.
+pub(crate) struct SettingDependency {
+    pub(crate) name: LitStr,
+    pub(crate) version: LitStr,
+}
+
+impl SettingDependency {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let (mut name, mut version) = (None, None);
+        meta.parse_nested_meta(|inner| {
+            if inner.path.is_ident("name") {
+                name = Some(inner.value()?.parse()?);
+            } else if inner.path.is_ident("version") {
+                version = Some(inner.value()?.parse()?);
+            } else {
+                return Err(inner.error("expected `name` or `version`"));
+            }
+            Ok(())
+        })?;
+
+        match (name, version) {
+            (Some(name), Some(version)) => Ok(Self { name, version }),
+            _ => Err(meta.error("setting dependencies require both a `name` and a `version`")),
+        }
+    }
+}
+
+impl ModelAttributes {
+    pub(crate) fn from_input(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attributes = ModelAttributes::default();
+
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("settings")) {
+            attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(|i| i.to_string())
+                    .unwrap_or_default();
+                match key.as_str() {
+                    "version" => attributes.version = Some(meta.value()?.parse()?),
+                    "partial" => attributes.partial = Some(meta.value()?.parse()?),
+                    "partial_derive" => meta.parse_nested_meta(|inner| {
+                        attributes.partial_derives.push(inner.path);
+                        Ok(())
+                    })?,
+                    "forward" => attributes.forward = Some(meta.value()?.parse()?),
+                    "backward" => attributes.backward = Some(meta.value()?.parse()?),
+                    "dependent_settings" => {
+                        attributes.dependent_settings = Some(meta.value()?.parse()?)
+                    }
+                    "migrate_forward" => attributes.migrate_forward = Some(meta.value()?.parse()?),
+                    "migrate_backward" => {
+                        attributes.migrate_backward = Some(meta.value()?.parse()?)
+                    }
+                    "set" => attributes.set = Some(meta.value()?.parse()?),
+                    "generate" => attributes.generate = Some(meta.value()?.parse()?),
+                    "validate" => attributes.validate = Some(meta.value()?.parse()?),
+                    "skip_validation_on_set" => attributes.skip_validation_on_set = true,
+                    "schema" => attributes.schema = true,
+                    "required_setting" => attributes
+                        .required_settings
+                        .push(SettingDependency::parse(&meta)?),
+                    "validated_setting" => attributes
+                        .validated_settings
+                        .push(SettingDependency::parse(&meta)?),
+                    _ => return Err(meta.error("unrecognized `settings` attribute")),
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attributes)
+    }
+
+    /// The model's partial type, which defaults to `<Name>Partial`.
+    pub(crate) fn partial_type(&self, name: &Ident) -> Type {
+        self.partial.clone().unwrap_or_else(|| {
+            let partial = default_partial_ident(name);
+            parse_quote!(#partial)
+        })
+    }
+
+    /// The name of the struct generated by `#[derive(SettingsPartial)]`.
+    pub(crate) fn partial_ident(&self, name: &Ident) -> syn::Result<Ident> {
+        match &self.partial {
+            None => Ok(default_partial_ident(name)),
+            Some(Type::Path(path)) if path.qself.is_none() => {
+                path.path.get_ident().cloned().ok_or_else(|| {
+                    syn::Error::new_spanned(path, "a derived `partial` must be a plain identifier")
+                })
+            }
+            Some(other) => Err(syn::Error::new_spanned(
+                other,
+                "a derived `partial` must be a plain identifier",
+            )),
+        }
+    }
+}
+
+fn default_partial_ident(name: &Ident) -> Ident {
+    format_ident!("{}Partial", name)
+}
@@ -0,0 +1,371 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Expr, Field, Fields, LitInt, LitStr, Meta, Path, Token, Type};
+
+/// Generates statements which record the result of each field's `#[settings(validate(...))]`
+/// checks in a `ValidationReport` named `report`, for a reference to a model named `value`.
+///
+/// Returns `None` if no field declares any checks.
+pub(crate) fn field_checks(input: &DeriveInput) -> syn::Result<Option<TokenStream>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    let rename_all = rename_all_rule(input)?;
+    let mut statements = Vec::new();
+    for field in fields {
+        let checks = FieldChecks::from_field(field)?;
+        if checks.checks.is_empty() {
+            continue;
+        }
+
+        let ident = &field.ident;
+        let name = serde_name(field, rename_all.as_ref())?;
+        let checks = checks
+            .checks
+            .iter()
+            .map(|check| quote!(report.check(#name, #check);));
+        statements.push(if is_option(&field.ty) {
+            quote! {
+                if let Some(field) = &value.#ident {
+                    #(#checks)*
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let field = &value.#ident;
+                    #(#checks)*
+                }
+            }
+        });
+    }
+
+    Ok((!statements.is_empty()).then(|| quote!(#(#statements)*)))
+}
+
+/// The checks declared for a single field, each of which is an expression producing a
+/// `CheckResult` for a reference named `field`.
+struct FieldChecks {
+    checks: Vec<TokenStream>,
+}
+
+impl FieldChecks {
+    fn from_field(field: &Field) -> syn::Result<Self> {
+        let checks_mod = quote!(::bottlerocket_settings_sdk::validation::checks);
+        let mut checks = Vec::new();
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("settings")) {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("validate") {
+                    return Err(meta.error("unrecognized `settings` field attribute"));
+                }
+
+                let (mut min, mut max) = (None, None);
+                meta.parse_nested_meta(|check| {
+                    let key = check
+                        .path
+                        .get_ident()
+                        .map(|i| i.to_string())
+                        .unwrap_or_default();
+                    match key.as_str() {
+                        "min_len" => {
+                            let min_len: LitInt = check.value()?.parse()?;
+                            checks.push(quote!(#checks_mod::min_len(field, #min_len)));
+                        }
+                        "max_len" => {
+                            let max_len: LitInt = check.value()?.parse()?;
+                            checks.push(quote!(#checks_mod::max_len(field, #max_len)));
+                        }
+                        "pattern" => {
+                            let pattern: LitStr = check.value()?.parse()?;
+                            checks.push(pattern_check(&pattern)?);
+                        }
+                        "min" => min = Some(check.value()?.parse::<Expr>()?),
+                        "max" => max = Some(check.value()?.parse::<Expr>()?),
+                        "one_of" => {
+                            let allowed = parse_list(&check)?;
+                            checks.push(quote!(#checks_mod::one_of(field, &[#(#allowed),*])));
+                        }
+                        "url" => checks.push(quote!(#checks_mod::url(field))),
+                        "hostname" => checks.push(quote!(#checks_mod::hostname(field))),
+                        "with" => {
+                            let function: Path = check.value()?.parse()?;
+                            checks.push(quote!(#function(field)));
+                        }
+                        _ => return Err(check.error("unrecognized validation check")),
+                    }
+                    Ok(())
+                })?;
+
+                match (min, max) {
+                    (None, None) => {}
+                    (Some(min), None) => checks.push(quote!(#checks_mod::range(field, #min..))),
+                    (None, Some(max)) => checks.push(quote!(#checks_mod::range(field, ..=#max))),
+                    (Some(min), Some(max)) => {
+                        checks.push(quote!(#checks_mod::range(field, #min..=#max)))
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(Self { checks })
+    }
+}
+
+/// Checks a field against a `pattern`, which is rejected at expansion time if it is not a valid
+/// regular expression. The pattern is compiled once, the first time it is checked.
+fn pattern_check(pattern: &LitStr) -> syn::Result<TokenStream> {
+    if let Err(e) = regex::Regex::new(&pattern.value()) {
+        return Err(syn::Error::new_spanned(
+            pattern,
+            format!("invalid `pattern`: {e}"),
+        ));
+    }
+
+    let sdk = quote!(::bottlerocket_settings_sdk);
+    let regex = quote!(#sdk::__private::regex::Regex);
+    Ok(quote! {
+        {
+            static PATTERN: ::std::sync::OnceLock<#regex> = ::std::sync::OnceLock::new();
+            let pattern = PATTERN.get_or_init(|| {
+                #regex::new(#pattern).expect("pattern was checked when the model was derived")
+            });
+            #sdk::validation::checks::matches(field, pattern)
+        }
+    })
+}
+
+/// Parses the bracketed list of a key such as `one_of = ["a", "b"]`.
+fn parse_list(meta: &ParseNestedMeta) -> syn::Result<Vec<Expr>> {
+    let list: syn::ExprArray = meta.value()?.parse()?;
+    Ok(list.elems.into_iter().collect())
+}
+
+/// Returns the model's `#[serde(rename_all = "...")]` rule, if any. For a rule given separately for
+/// serialization and deserialization, the serialization rule is used.
+fn rename_all_rule(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut rule = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in metas {
+            match meta {
+                Meta::NameValue(name_value) if name_value.path.is_ident("rename_all") => {
+                    rule = Some(string_value(&name_value.value)?);
+                }
+                Meta::List(list) if list.path.is_ident("rename_all") => {
+                    list.parse_nested_meta(|inner| {
+                        let value: LitStr = inner.value()?.parse()?;
+                        if inner.path.is_ident("serialize") {
+                            rule = Some(value);
+                        }
+                        Ok(())
+                    })?;
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(rule)
+}
+
+/// Returns the name of a field as it is serialized, honoring `#[serde(rename = "...")]` and the
+/// model's `rename_all` rule.
+fn serde_name(field: &Field, rename_all: Option<&LitStr>) -> syn::Result<String> {
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in metas {
+            if let Meta::NameValue(name_value) = meta {
+                if name_value.path.is_ident("rename") {
+                    return Ok(string_value(&name_value.value)?.value());
+                }
+            }
+        }
+    }
+
+    let name = field
+        .ident
+        .as_ref()
+        .map(|ident| ident.unraw().to_string())
+        .unwrap_or_default();
+    match rename_all {
+        Some(rule) => apply_rename_rule(rule, &name),
+        None => Ok(name),
+    }
+}
+
+/// Renames a snake_case field name according to a serde `rename_all` rule.
+fn apply_rename_rule(rule: &LitStr, name: &str) -> syn::Result<String> {
+    let pascal_case = || {
+        name.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect::<String>()
+    };
+
+    Ok(match rule.value().as_str() {
+        "lowercase" | "snake_case" => name.to_string(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal_case(),
+        "camelCase" => {
+            let pascal = pascal_case();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        }
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => return Err(syn::Error::new_spanned(rule, "unknown `rename_all` rule")),
+    })
+}
+
+/// Returns the string literal given as the value of a serde attribute.
+fn string_value(value: &Expr) -> syn::Result<LitStr> {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(value),
+            ..
+        }) => Ok(value.clone()),
+        other => Err(syn::Error::new_spanned(other, "expected a string literal")),
+    }
+}
+
+/// Returns whether a type is spelled as `Option<...>`. Checks on optional fields only apply to
+/// populated values.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_rename_rule, field_checks};
+    use proc_macro2::Span;
+    use syn::{parse_quote, DeriveInput, LitStr};
+
+    fn checks_error(input: DeriveInput) -> String {
+        field_checks(&input)
+            .expect_err("field checks should fail")
+            .to_string()
+    }
+
+    #[test]
+    fn no_checks() {
+        let input = parse_quote! {
+            struct Motd {
+                motd: String,
+            }
+        };
+        assert!(field_checks(&input).unwrap().is_none());
+    }
+
+    #[test]
+    fn renamed_optional_field() {
+        let tokens = field_checks(&parse_quote! {
+            struct Motd {
+                #[serde(rename = "message")]
+                #[settings(validate(min_len = 1, min = 0, max = 10))]
+                motd: Option<String>,
+            }
+        })
+        .unwrap()
+        .unwrap()
+        .to_string();
+        assert!(tokens.contains("if let Some (field) = & value . motd"));
+        assert!(tokens.contains("report . check (\"message\""));
+        assert!(tokens.contains("range (field , 0 ..= 10)"));
+    }
+
+    #[test]
+    fn rename_all() {
+        let tokens = field_checks(&parse_quote! {
+            #[serde(rename_all = "kebab-case")]
+            struct Motd {
+                #[settings(validate(min_len = 1))]
+                line_count: String,
+                #[serde(rename = "by")]
+                #[settings(validate(min_len = 1))]
+                signed_by: String,
+            }
+        })
+        .unwrap()
+        .unwrap()
+        .to_string();
+        assert!(tokens.contains("report . check (\"line-count\""));
+        assert!(tokens.contains("report . check (\"by\""));
+    }
+
+    #[test]
+    fn rename_rules() {
+        let name = |rule: &str| {
+            apply_rename_rule(&LitStr::new(rule, Span::call_site()), "max_line_count").unwrap()
+        };
+        assert_eq!(name("lowercase"), "max_line_count");
+        assert_eq!(name("UPPERCASE"), "MAX_LINE_COUNT");
+        assert_eq!(name("PascalCase"), "MaxLineCount");
+        assert_eq!(name("camelCase"), "maxLineCount");
+        assert_eq!(name("snake_case"), "max_line_count");
+        assert_eq!(name("SCREAMING_SNAKE_CASE"), "MAX_LINE_COUNT");
+        assert_eq!(name("kebab-case"), "max-line-count");
+        assert_eq!(name("SCREAMING-KEBAB-CASE"), "MAX-LINE-COUNT");
+        assert!(apply_rename_rule(&LitStr::new("Title Case", Span::call_site()), "motd").is_err());
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let error = checks_error(parse_quote! {
+            struct Motd {
+                #[settings(validate(pattern = "[a-z"))]
+                motd: String,
+            }
+        });
+        assert!(error.starts_with("invalid `pattern`: "), "{}", error);
+    }
+
+    #[test]
+    fn unrecognized_check() {
+        assert_eq!(
+            checks_error(parse_quote! {
+                struct Motd {
+                    #[settings(validate(colour = "blue"))]
+                    motd: String,
+                }
+            }),
+            "unrecognized validation check"
+        );
+    }
+
+    #[test]
+    fn unrecognized_field_attribute() {
+        assert_eq!(
+            checks_error(parse_quote! {
+                struct Motd {
+                    #[settings(skip)]
+                    motd: String,
+                }
+            }),
+            "unrecognized `settings` field attribute"
+        );
+    }
+}
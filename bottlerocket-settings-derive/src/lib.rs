@@ -0,0 +1,117 @@
+//! Derive macros for implementing Bottlerocket settings models.
+//!
+//! These macros are re-exported by `bottlerocket-settings-sdk`, and should be used from there.
+mod attributes;
+mod field_validation;
+mod partial;
+mod settings_model;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implements `SettingsModel` for a struct, driven by a `#[settings(...)]` attribute.
+///
+/// ```
+/// # use bottlerocket_settings_sdk::{SettingsModel, SettingsPartial};
+/// # use serde::{Deserialize, Serialize};
+/// # #[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+/// # #[settings(version = "v1", forward = MotdV2, migrate_forward = migrate_to_v2)]
+/// # struct MotdV1 { motd: String }
+/// # fn migrate_to_v2(v1: MotdV1) -> anyhow::Result<MotdV2> {
+/// #     Ok(MotdV2 { motd: v1.motd, author: String::new() })
+/// # }
+/// #[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+/// #[settings(
+///     version = "v2",
+///     backward = MotdV1,
+///     migrate_backward = migrate_to_v1,
+/// )]
+/// struct MotdV2 {
+///     motd: String,
+///     author: String,
+/// }
+///
+/// fn migrate_to_v1(v2: MotdV2) -> anyhow::Result<MotdV1> {
+///     Ok(MotdV1 { motd: v2.motd })
+/// }
+///
+/// assert_eq!(MotdV2::VERSION, "v2");
+/// assert_eq!(MotdV2::migrates_backward_to(), Some("v1"));
+/// ```
+///
+/// The following keys are accepted:
+///
+/// * `version` (required): the version string of the model, also emitted as `Self::VERSION`.
+/// * `partial`: the model's `PartialType`. Defaults to `<Name>Partial`, which is the type generated
+///   by `#[derive(SettingsPartial)]`.
+/// * `partial_derive(...)`: further traits derived by the type generated by
+///   `#[derive(SettingsPartial)]`.
+/// * `forward` / `backward`: the migration target types. Each defaults to `NoMigration`.
+/// * `migrate_forward` / `migrate_backward`: functions performing the migration to `forward` or
+///   `backward`. These are required if, and only if, the corresponding target is given.
+/// * `dependent_settings`: the model's `DependentSettings` type, which the settings named by
+///   `required_setting` and `validated_setting` are deserialized into. Defaults to
+///   `NoDependentSettings`.
+/// * `set`: `fn(Option<Self>, Self) -> Result<Self>`. Defaults to accepting the target value.
+/// * `generate`: `fn(Option<PartialType>, Option<DependentSettings>) -> Result<GenerateResult>`.
+///   Defaults to `GenerateResult::from_partial`, which completes the existing partial if every
+///   field is populated. This default requires the partial type to implement `Partial<Self>`.
+/// * `validate`: `fn(Self, Option<DependentSettings>) -> Result<ValidationReport>`. Defaults to an
+///   empty report.
+/// * `schema`: publishes the model's JSON Schema. This requires the SDK's `schemars` feature, and
+///   the model must implement `schemars::JsonSchema`.
+/// * `skip_validation_on_set`: stops the SDK from validating values produced by `set` before
+///   accepting them.
+/// * `required_setting(name = "...", version = "...")`: a setting needed by `generate`. May be
+///   repeated.
+/// * `validated_setting(name = "...", version = "...")`: a setting needed by `validate`. May be
+///   repeated.
+///
+/// Fields may declare checks with `#[settings(validate(...))]`, which are reported by `validate`
+/// before any checks made by the model's own `validate` function. They are also reported by
+/// `validate_fields`, which `set` uses when the model's validated settings are not provided.
+/// Checks on `Option` fields only apply when the field is populated.
+///
+/// ```ignore
+/// #[settings(validate(min_len = 1, max_len = 255, pattern = "^[[:print:]]*$"))]
+/// motd: String,
+/// ```
+///
+/// * `min_len` / `max_len`: bounds on the length of a string or collection.
+/// * `pattern`: a regular expression which the field must match. An invalid pattern fails to
+///   compile.
+/// * `min` / `max`: bounds on the field's value.
+/// * `one_of = [...]`: the values which the field may take.
+/// * `url` / `hostname`: the field must be a URL or a hostname.
+/// * `with = path`: a function `fn(&T) -> CheckResult` performing a custom check.
+///
+/// Issues are reported against the field's name as it is serialized, following its
+/// `#[serde(rename = "...")]` or the model's `#[serde(rename_all = "...")]`.
+#[proc_macro_derive(SettingsModel, attributes(settings))]
+pub fn derive_settings_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    settings_model::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generates the partial type of a settings model, in which every field is wrapped in `Option`.
+///
+/// The generated struct is named `<Name>Partial` unless `#[settings(partial = ...)]` names it
+/// otherwise. It implements `From<Name>` and `Partial<Name>`, which allows merging a partial into a
+/// complete value and promoting a fully populated partial to a complete value.
+///
+/// The partial type derives `Debug`, `Default`, `Serialize` and `Deserialize`. Further traits can be
+/// derived with `#[settings(partial_derive(Clone, PartialEq))]`.
+///
+/// The model's `rename_all` and `bound` serde attributes are copied to the partial type, as are the
+/// `rename` and `alias` attributes of each field. The `crate`, `default` and `deny_unknown_fields`
+/// attributes of the model are not copied, and any other container `serde` attribute, such as
+/// `try_from` or `tag`, fails to compile.
+#[proc_macro_derive(SettingsPartial, attributes(settings))]
+pub fn derive_settings_partial(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    partial::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,252 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DeriveInput, Fields, Meta, Token};
+
+use crate::attributes::ModelAttributes;
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attributes = ModelAttributes::from_input(&input)?;
+    let sdk = quote!(::bottlerocket_settings_sdk);
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let partial = attributes.partial_ident(name)?;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`SettingsPartial` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`SettingsPartial` can only be derived for structs",
+            ))
+        }
+    };
+
+    let struct_serde_attrs = container_naming_attrs(&input.attrs)?;
+    let derives = &attributes.partial_derives;
+    let partial_fields = fields
+        .iter()
+        .map(|field| {
+            let field_vis = &field.vis;
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let serde_attrs = field_naming_attrs(&field.attrs)?;
+            Ok(quote! {
+                #serde_attrs
+                #field_vis #ident: Option<#ty>
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let doc =
+        format!("Partially populated version of [`{name}`], in which every field is optional.");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(
+            Debug,
+            Default,
+            #sdk::__private::serde::Serialize,
+            #sdk::__private::serde::Deserialize,
+            #(#derives,)*
+        )]
+        #[serde(crate = "::bottlerocket_settings_sdk::__private::serde")]
+        #struct_serde_attrs
+        #vis struct #partial #generics #where_clause {
+            #(#partial_fields,)*
+        }
+
+        impl #impl_generics From<#name #ty_generics> for #partial #ty_generics #where_clause {
+            fn from(complete: #name #ty_generics) -> Self {
+                Self {
+                    #(#idents: Some(complete.#idents),)*
+                }
+            }
+        }
+
+        impl #impl_generics #sdk::Partial<#name #ty_generics> for #partial #ty_generics #where_clause {
+            fn merge_into(self, complete: #name #ty_generics) -> #name #ty_generics {
+                #name {
+                    #(#idents: self.#idents.unwrap_or(complete.#idents),)*
+                }
+            }
+
+            fn try_complete(self) -> ::std::result::Result<#name #ty_generics, Self> {
+                match self {
+                    Self { #(#idents: Some(#idents),)* } => Ok(#name { #(#idents,)* }),
+                    partial => Err(partial),
+                }
+            }
+        }
+    })
+}
+
+/// Container serde attributes which only affect how the complete model is deserialized, and so are
+/// not carried over to the partial type.
+const IGNORED_CONTAINER_ATTRS: &[&str] = &["crate", "default", "deny_unknown_fields"];
+
+/// Returns the `rename_all` serde attribute of the model, so that the partial type uses the same
+/// field names as the complete model, and any `bound` attribute needed by its generic fields.
+///
+/// Attributes which change how the model is represented, such as `try_from` or `tag`, cannot be
+/// applied to a partial type whose fields are all optional, and are rejected.
+fn container_naming_attrs(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let mut naming = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in metas {
+            if meta.path().is_ident("rename_all") || meta.path().is_ident("bound") {
+                naming.push(meta);
+            } else if !IGNORED_CONTAINER_ATTRS
+                .iter()
+                .any(|ignored| meta.path().is_ident(ignored))
+            {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "`SettingsPartial` does not support this `serde` attribute on the model",
+                ));
+            }
+        }
+    }
+
+    Ok(if naming.is_empty() {
+        quote!()
+    } else {
+        quote!(#[serde(#(#naming),*)])
+    })
+}
+
+/// Returns the `rename` and `alias` serde attributes of a field, so that the partial type uses the
+/// same field names as the complete model. Other serde field attributes, such as
+/// `deserialize_with`, are specific to the complete field type and are not carried over.
+fn field_naming_attrs(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let mut naming = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        naming.extend(
+            metas
+                .into_iter()
+                .filter(|meta| meta.path().is_ident("rename") || meta.path().is_ident("alias")),
+        );
+    }
+
+    Ok(if naming.is_empty() {
+        quote!()
+    } else {
+        quote!(#[serde(#(#naming),*)])
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::expand;
+    use quote::quote;
+    use syn::{parse_quote, DeriveInput};
+
+    fn expand_error(input: DeriveInput) -> String {
+        expand(input)
+            .expect_err("expansion should fail")
+            .to_string()
+    }
+
+    #[test]
+    fn copies_naming_attributes() {
+        let tokens = expand(parse_quote! {
+            #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+            struct Motd {
+                #[serde(rename = "message", default, deserialize_with = "custom")]
+                motd: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(tokens.contains("# [serde (rename_all = \"kebab-case\")]"));
+        assert!(tokens.contains("# [serde (rename = \"message\")] motd : Option < String >"));
+        assert!(!tokens.contains("deserialize_with"));
+        assert!(!tokens.contains("deny_unknown_fields"));
+    }
+
+    #[test]
+    fn unsupported_container_attribute() {
+        for attr in [
+            quote!(#[serde(try_from = "String")]),
+            quote!(#[serde(tag = "type")]),
+            quote!(#[serde(rename_all = "kebab-case", transparent)]),
+        ] {
+            let input = parse_quote! {
+                #attr
+                struct Motd {
+                    motd: String,
+                }
+            };
+            assert_eq!(
+                expand_error(input),
+                "`SettingsPartial` does not support this `serde` attribute on the model"
+            );
+        }
+    }
+
+    #[test]
+    fn partial_derives() {
+        let tokens = expand(parse_quote! {
+            struct Motd {
+                motd: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(!tokens.contains("Clone"));
+
+        let tokens = expand(parse_quote! {
+            #[settings(version = "v1", partial_derive(Clone, PartialEq))]
+            struct Motd {
+                motd: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(tokens.contains("Clone , PartialEq ,"));
+    }
+
+    #[test]
+    fn tuple_struct() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                struct Motd(String);
+            }),
+            "`SettingsPartial` can only be derived for structs with named fields"
+        );
+    }
+
+    #[test]
+    fn enum_model() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                enum Motd { Short, Long }
+            }),
+            "`SettingsPartial` can only be derived for structs"
+        );
+    }
+
+    #[test]
+    fn partial_must_be_an_identifier() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                #[settings(version = "v1", partial = partials::Motd)]
+                struct Motd {}
+            }),
+            "a derived `partial` must be a plain identifier"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Path, Type};
+
+use crate::attributes::{ModelAttributes, SettingDependency};
+use crate::field_validation;
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attributes = ModelAttributes::from_input(&input)?;
+    let sdk = quote!(::bottlerocket_settings_sdk);
+    let anyhow = quote!(#sdk::__private::anyhow);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // A derived partial type has the same generic parameters as the model.
+    let partial = match &attributes.partial {
+        Some(partial) => quote!(#partial),
+        None => {
+            let partial = attributes.partial_type(name);
+            quote!(#partial #ty_generics)
+        }
+    };
+    let version = attributes.version.ok_or_else(|| {
+        syn::Error::new_spanned(name, "missing `#[settings(version = \"...\")]` attribute")
+    })?;
+
+    let dependent_settings = attributes
+        .dependent_settings
+        .map(|dependent_settings| quote!(#dependent_settings))
+        .unwrap_or_else(|| quote!(#sdk::NoDependentSettings));
+
+    let (forward, migrate_forward) = migration(
+        name,
+        attributes.forward,
+        attributes.migrate_forward,
+        "forward",
+        "migrate_forward",
+    )?;
+    let (backward, migrate_backward) = migration(
+        name,
+        attributes.backward,
+        attributes.migrate_backward,
+        "backward",
+        "migrate_backward",
+    )?;
+
+    let set = attributes
+        .set
+        .map(|set| quote!(#set(current_value, target)))
+        .unwrap_or_else(|| quote!(Ok(target)));
+    let generate = attributes
+        .generate
+        .map(|generate| quote!(#generate(existing_partial, dependent_settings)))
+        .unwrap_or_else(|| quote!(Ok(#sdk::GenerateResult::from_partial(existing_partial))));
+    let field_checks = field_validation::field_checks(&input)?;
+    let validate = attributes
+        .validate
+        .map(|validate| quote!(#validate(value, validated_settings)))
+        .unwrap_or_else(|| quote!(Ok(#sdk::ValidationReport::new())));
+    let (validate, validate_fields) = match &field_checks {
+        None => (validate, None),
+        Some(field_checks) => (
+            quote! {
+                let mut report = Self::validate_fields(&value);
+                let model_report: #anyhow::Result<#sdk::ValidationReport> = #validate;
+                report.merge("", model_report?);
+                Ok(report)
+            },
+            Some(quote! {
+                fn validate_fields(value: &Self) -> #sdk::ValidationReport {
+                    let mut report = #sdk::ValidationReport::new();
+                    #field_checks
+                    report
+                }
+            }),
+        ),
+    };
+
+    let dependencies = |dependencies: &[SettingDependency]| {
+        let dependencies = dependencies.iter().map(|SettingDependency { name, version }| {
+            quote!(#sdk::SettingDependency::new(#name, #version))
+        });
+        quote!(&[#(#dependencies),*])
+    };
+    let required_settings = dependencies(&attributes.required_settings);
+    let validated_settings = dependencies(&attributes.validated_settings);
+    let validate_on_set = !attributes.skip_validation_on_set;
+    let json_schema = attributes.schema.then(|| {
+        quote! {
+            fn json_schema() -> Option<#sdk::__private::serde_json::Value> {
+                Some(#sdk::schema::json_schema_for::<Self>())
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The settings version implemented by this model.
+            pub const VERSION: &'static str = #version;
+        }
+
+        impl #impl_generics #sdk::SettingsModel for #name #ty_generics #where_clause {
+            type PartialType = #partial;
+            type ForwardMigrationTarget = #forward;
+            type BackwardMigrationTarget = #backward;
+            type DependentSettings = #dependent_settings;
+
+            const REQUIRED_SETTINGS: &'static [#sdk::SettingDependency] = #required_settings;
+            const VALIDATED_SETTINGS: &'static [#sdk::SettingDependency] = #validated_settings;
+            const VALIDATE_ON_SET: bool = #validate_on_set;
+
+            fn get_version() -> &'static str {
+                Self::VERSION
+            }
+
+            #[allow(unused_variables)]
+            fn set(current_value: Option<Self>, target: Self) -> #anyhow::Result<Self> {
+                #set
+            }
+
+            #[allow(unused_variables)]
+            fn generate(
+                existing_partial: Option<Self::PartialType>,
+                dependent_settings: Option<Self::DependentSettings>,
+            ) -> #anyhow::Result<#sdk::GenerateResult<Self::PartialType, Self>> {
+                #generate
+            }
+
+            #[allow(unused_variables)]
+            fn validate(
+                value: Self,
+                validated_settings: Option<Self::DependentSettings>,
+            ) -> #anyhow::Result<#sdk::ValidationReport> {
+                #validate
+            }
+
+            #validate_fields
+
+            #json_schema
+
+            fn migrate_forward(self) -> #anyhow::Result<Self::ForwardMigrationTarget> {
+                #migrate_forward
+            }
+
+            fn migrate_backward(self) -> #anyhow::Result<Self::BackwardMigrationTarget> {
+                #migrate_backward
+            }
+        }
+    })
+}
+
+/// Resolves the migration target type and the body of the migration function for one direction.
+///
+/// A migration function must be given if and only if a migration target is given.
+fn migration(
+    name: &syn::Ident,
+    target: Option<Type>,
+    function: Option<Path>,
+    target_key: &str,
+    function_key: &str,
+) -> syn::Result<(TokenStream, TokenStream)> {
+    let sdk = quote!(::bottlerocket_settings_sdk);
+    match (target, function) {
+        (Some(target), Some(function)) => Ok((quote!(#target), quote!(#function(self)))),
+        (None, None) => Ok((
+            quote!(#sdk::NoMigration),
+            quote!(#sdk::NoMigration::no_defined_migration()),
+        )),
+        (Some(target), None) => Err(syn::Error::new_spanned(
+            target,
+            format!("`{target_key}` requires a `{function_key}` function"),
+        )),
+        (None, Some(function)) => Err(syn::Error::new_spanned(
+            function,
+            format!("`{function_key}` requires a `{target_key}` migration target (on `{name}`)"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::expand;
+    use syn::{parse_quote, DeriveInput};
+
+    fn expand_error(input: DeriveInput) -> String {
+        expand(input)
+            .expect_err("expansion should fail")
+            .to_string()
+    }
+
+    #[test]
+    fn expands_model() {
+        let tokens = expand(parse_quote! {
+            #[settings(version = "v1", forward = MotdV2, migrate_forward = to_v2)]
+            struct MotdV1 {
+                motd: String,
+            }
+        })
+        .unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+        assert_eq!(file.items.len(), 2);
+
+        let item = &file.items[1];
+        let tokens = quote::quote!(#item).to_string();
+        assert!(tokens.contains("type PartialType = MotdV1Partial ;"));
+        assert!(tokens.contains("type ForwardMigrationTarget = MotdV2 ;"));
+        assert!(tokens.contains("to_v2 (self)"));
+    }
+
+    #[test]
+    fn generic_partial_type() {
+        let tokens = expand(parse_quote! {
+            #[settings(version = "v1")]
+            struct Wrapped<T> {
+                value: T,
+            }
+        })
+        .unwrap();
+        assert!(tokens
+            .to_string()
+            .contains("type PartialType = WrappedPartial < T > ;"));
+    }
+
+    #[test]
+    fn missing_version() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                struct Motd {}
+            }),
+            "missing `#[settings(version = \"...\")]` attribute"
+        );
+    }
+
+    #[test]
+    fn forward_without_migrate_forward() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                #[settings(version = "v1", forward = MotdV2)]
+                struct Motd {}
+            }),
+            "`forward` requires a `migrate_forward` function"
+        );
+    }
+
+    #[test]
+    fn migrate_backward_without_backward() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                #[settings(version = "v2", migrate_backward = to_v1)]
+                struct Motd {}
+            }),
+            "`migrate_backward` requires a `backward` migration target (on `Motd`)"
+        );
+    }
+
+    #[test]
+    fn unrecognized_attribute() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                #[settings(version = "v1", colour = "blue")]
+                struct Motd {}
+            }),
+            "unrecognized `settings` attribute"
+        );
+    }
+
+    #[test]
+    fn incomplete_setting_dependency() {
+        assert_eq!(
+            expand_error(parse_quote! {
+                #[settings(version = "v1", required_setting(name = "network"))]
+                struct Motd {}
+            }),
+            "setting dependencies require both a `name` and a `version`"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use bottlerocket_settings_sdk::validation::CheckResult;
+use bottlerocket_settings_sdk::{
+    NoDependentSettings, SettingsModel, SettingsPartial, ValidationReport,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1", validate = no_motd_issues)]
+struct Motd {
+    #[settings(validate(min_len = 1, max_len = 16, pattern = "^[a-z ]*$"))]
+    motd: String,
+    #[serde(rename = "lines")]
+    #[settings(validate(min = 1, max = 3))]
+    line_count: u8,
+    #[settings(validate(one_of = ["left", "center"]))]
+    align: String,
+    #[settings(validate(url))]
+    link: Option<String>,
+    #[settings(validate(with = not_shouting))]
+    signature: String,
+}
+
+fn no_motd_issues(value: Motd, _: Option<NoDependentSettings>) -> anyhow::Result<ValidationReport> {
+    let mut report = ValidationReport::new();
+    if value.signature.is_empty() {
+        report.warning("signature", "the motd is unsigned");
+    }
+    Ok(report)
+}
+
+fn not_shouting(value: &str) -> CheckResult {
+    if value.chars().any(char::is_uppercase) {
+        return Err("must not be shouted".to_string());
+    }
+    Ok(())
+}
+
+fn motd() -> Motd {
+    Motd {
+        motd: "hello there".to_string(),
+        line_count: 1,
+        align: "left".to_string(),
+        link: None,
+        signature: "me".to_string(),
+    }
+}
+
+fn invalid_fields(value: Motd) -> Vec<String> {
+    let report = Motd::validate(value, None).unwrap();
+    assert!(!report.is_valid());
+    report.errors().map(|issue| issue.field.clone()).collect()
+}
+
+#[test]
+fn valid() {
+    let report = Motd::validate(motd(), None).unwrap();
+    assert!(report.is_valid());
+    assert!(report.issues().is_empty());
+}
+
+#[test]
+fn string_checks() {
+    let too_long = Motd {
+        motd: "hello there everyone".to_string(),
+        ..motd()
+    };
+    assert_eq!(invalid_fields(too_long), ["motd"]);
+
+    let mismatched = Motd {
+        motd: "Hello!".to_string(),
+        ..motd()
+    };
+    assert_eq!(invalid_fields(mismatched), ["motd"]);
+
+    // The cached pattern is reused by later checks.
+    let empty = Motd {
+        motd: String::new(),
+        ..motd()
+    };
+    assert_eq!(invalid_fields(empty), ["motd"]);
+}
+
+#[test]
+fn renamed_field() {
+    let value = Motd {
+        line_count: 4,
+        ..motd()
+    };
+    assert_eq!(invalid_fields(value), ["lines"]);
+}
+
+#[test]
+fn one_of() {
+    let value = Motd {
+        align: "right".to_string(),
+        ..motd()
+    };
+    assert_eq!(invalid_fields(value), ["align"]);
+}
+
+#[test]
+fn optional_field() {
+    let value = Motd {
+        link: Some("not a url".to_string()),
+        ..motd()
+    };
+    assert_eq!(invalid_fields(value), ["link"]);
+
+    let value = Motd {
+        link: Some("https://example.com".to_string()),
+        ..motd()
+    };
+    assert!(Motd::validate(value, None).unwrap().is_valid());
+}
+
+#[test]
+fn custom_check() {
+    let value = Motd {
+        signature: "ME".to_string(),
+        ..motd()
+    };
+    let report = Motd::validate(value, None).unwrap();
+    let issues: Vec<_> = report.errors().collect();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].field, "signature");
+    assert_eq!(issues[0].message, "must not be shouted");
+}
+
+#[test]
+fn merged_with_model_report() {
+    let value = Motd {
+        signature: String::new(),
+        line_count: 0,
+        ..motd()
+    };
+    let report = Motd::validate(value, None).unwrap();
+    assert_eq!(
+        report
+            .errors()
+            .map(|i| i.field.as_str())
+            .collect::<Vec<_>>(),
+        ["lines"]
+    );
+    assert_eq!(
+        report
+            .warnings()
+            .map(|i| i.field.as_str())
+            .collect::<Vec<_>>(),
+        ["signature"]
+    );
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v1")]
+#[serde(rename_all = "kebab-case")]
+struct Banner {
+    #[settings(validate(min = 1))]
+    line_count: u8,
+    #[serde(rename = "text")]
+    #[settings(validate(min_len = 1))]
+    banner_text: String,
+}
+
+#[test]
+fn field_paths_follow_rename_all() {
+    let banner = Banner {
+        line_count: 0,
+        banner_text: String::new(),
+    };
+    let report = Banner::validate_fields(&banner);
+    let fields: Vec<_> = report.errors().map(|issue| issue.field.as_str()).collect();
+    assert_eq!(fields, ["line-count", "text"]);
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use bottlerocket_settings_sdk::{
+    GenerateResult, NoDependentSettings, NoMigration, Partial, SettingDependency, SettingsModel,
+    SettingsPartial, ValidationReport,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(
+    version = "v1",
+    forward = MotdV2,
+    migrate_forward = to_v2,
+    partial_derive(PartialEq)
+)]
+struct MotdV1 {
+    motd: String,
+}
+
+fn to_v2(value: MotdV1) -> Result<MotdV2> {
+    Ok(MotdV2 {
+        motd: value.motd,
+        author: "unknown".to_string(),
+    })
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(
+    version = "v2",
+    backward = MotdV1,
+    migrate_backward = to_v1,
+    set = set_v2,
+    validate = validate_v2,
+    dependent_settings = Hostname,
+    required_setting(name = "hostname", version = "v1"),
+    validated_setting(name = "hostname", version = "v1"),
+    skip_validation_on_set
+)]
+struct MotdV2 {
+    motd: String,
+    author: String,
+}
+
+fn to_v1(value: MotdV2) -> Result<MotdV1> {
+    Ok(MotdV1 { motd: value.motd })
+}
+
+/// Keeps the current author, if there is one.
+fn set_v2(current: Option<MotdV2>, target: MotdV2) -> Result<MotdV2> {
+    Ok(match current {
+        Some(current) => MotdV2 {
+            author: current.author,
+            ..target
+        },
+        None => target,
+    })
+}
+
+fn validate_v2(value: MotdV2, hostname: Option<Hostname>) -> Result<ValidationReport> {
+    let mut report = ValidationReport::new();
+    if let Some(hostname) = hostname {
+        if value.motd.contains(&hostname.hostname) {
+            report.warning("motd", "mentions the hostname");
+        }
+    }
+    Ok(report)
+}
+
+#[derive(Deserialize, Debug)]
+struct Hostname {
+    hostname: String,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1", partial_derive(PartialEq))]
+#[serde(bound = "")]
+struct Labelled<T>
+where
+    T: Serialize + DeserializeOwned + Debug + Clone + PartialEq,
+{
+    label: String,
+    value: T,
+}
+
+#[test]
+fn version() {
+    assert_eq!(MotdV1::VERSION, "v1");
+    assert_eq!(MotdV1::get_version(), "v1");
+    assert_eq!(MotdV2::get_version(), "v2");
+}
+
+#[test]
+fn migrations() {
+    assert_eq!(MotdV1::migrates_forward_to(), Some("v2"));
+    assert_eq!(MotdV1::migrates_backward_to(), None);
+    assert_eq!(MotdV2::migrates_forward_to(), None);
+    assert_eq!(MotdV2::migrates_backward_to(), Some("v1"));
+
+    let v1 = MotdV1 {
+        motd: "hi".to_string(),
+    };
+    let v2 = v1.clone().migrate_forward().unwrap();
+    assert_eq!(v2.author, "unknown");
+    assert_eq!(v2.migrate_backward().unwrap(), v1);
+    assert_eq!(
+        MotdV2 {
+            motd: "hi".to_string(),
+            author: "a".to_string()
+        }
+        .migrate_forward()
+        .unwrap(),
+        NoMigration
+    );
+}
+
+#[test]
+fn default_set_accepts_target() {
+    let current = MotdV1 {
+        motd: "old".to_string(),
+    };
+    let target = MotdV1 {
+        motd: "new".to_string(),
+    };
+    assert_eq!(MotdV1::set(Some(current), target.clone()).unwrap(), target);
+}
+
+#[test]
+fn custom_set() {
+    let current = MotdV2 {
+        motd: "old".to_string(),
+        author: "me".to_string(),
+    };
+    let target = MotdV2 {
+        motd: "new".to_string(),
+        author: "you".to_string(),
+    };
+    let set = MotdV2::set(Some(current), target).unwrap();
+    assert_eq!(set.motd, "new");
+    assert_eq!(set.author, "me");
+}
+
+#[test]
+fn default_generate_completes_partial() {
+    let complete = MotdV1Partial {
+        motd: Some("hi".to_string()),
+    };
+    assert_eq!(
+        MotdV1::generate(Some(complete), None).unwrap(),
+        GenerateResult::Complete(Some(MotdV1 {
+            motd: "hi".to_string()
+        }))
+    );
+    assert_eq!(
+        MotdV1::generate(Some(MotdV1Partial::default()), None).unwrap(),
+        GenerateResult::NeedsData(Some(MotdV1Partial::default()))
+    );
+    assert_eq!(
+        MotdV1::generate(None, None).unwrap(),
+        GenerateResult::NeedsData(None)
+    );
+}
+
+#[test]
+fn default_validate_reports_nothing() {
+    let report = MotdV1::validate(
+        MotdV1 {
+            motd: "hi".to_string(),
+        },
+        None::<NoDependentSettings>,
+    )
+    .unwrap();
+    assert!(report.is_valid());
+    assert!(report.issues().is_empty());
+}
+
+#[test]
+fn custom_validate_receives_dependent_settings() {
+    let value = MotdV2 {
+        motd: "welcome to host-1".to_string(),
+        author: "me".to_string(),
+    };
+    let hostname = Hostname {
+        hostname: "host-1".to_string(),
+    };
+    let report = MotdV2::validate(value, Some(hostname)).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.warnings().count(), 1);
+}
+
+#[test]
+fn setting_dependencies() {
+    assert_eq!(MotdV1::REQUIRED_SETTINGS, &[]);
+    assert_eq!(
+        MotdV2::REQUIRED_SETTINGS,
+        &[SettingDependency::new("hostname", "v1")]
+    );
+    assert_eq!(
+        MotdV2::VALIDATED_SETTINGS,
+        &[SettingDependency::new("hostname", "v1")]
+    );
+}
+
+#[test]
+fn validate_on_set() {
+    const { assert!(MotdV1::VALIDATE_ON_SET) };
+    const { assert!(!MotdV2::VALIDATE_ON_SET) };
+}
+
+#[test]
+fn json_schema_is_not_published_by_default() {
+    assert_eq!(MotdV1::json_schema(), None);
+}
+
+#[test]
+fn generic_model() {
+    assert_eq!(Labelled::<u32>::get_version(), "v1");
+    let partial = LabelledPartial::<u32> {
+        label: Some("port".to_string()),
+        value: Some(80),
+    };
+    assert_eq!(
+        Labelled::<u32>::generate(Some(partial), None).unwrap(),
+        GenerateResult::Complete(Some(Labelled {
+            label: "port".to_string(),
+            value: 80
+        }))
+    );
+
+    let partial = LabelledPartial::<u32> {
+        label: None,
+        value: Some(443),
+    };
+    let merged = partial.merge_into(Labelled {
+        label: "port".to_string(),
+        value: 80,
+    });
+    assert_eq!(merged.value, 443);
+}
@@ -0,0 +1,108 @@
+use bottlerocket_settings_sdk::{Partial, SettingsModel, SettingsPartial};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1", partial_derive(Clone, PartialEq))]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Motd {
+    message_text: String,
+    #[serde(rename = "by")]
+    author: String,
+    #[serde(alias = "shown")]
+    enabled: bool,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1", partial = Fragment, partial_derive(PartialEq))]
+struct Banner {
+    text: String,
+}
+
+/// A field type which is neither `Clone` nor `PartialEq`.
+#[derive(Serialize, Deserialize, Debug)]
+struct Handle(u32);
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v1")]
+struct Connection {
+    handle: Handle,
+}
+
+fn motd() -> Motd {
+    Motd {
+        message_text: "hi".to_string(),
+        author: "me".to_string(),
+        enabled: true,
+    }
+}
+
+#[test]
+fn from_complete() {
+    let partial = MotdPartial::from(motd());
+    assert_eq!(partial.message_text.as_deref(), Some("hi"));
+    assert_eq!(partial.author.as_deref(), Some("me"));
+    assert_eq!(partial.enabled, Some(true));
+}
+
+#[test]
+fn merge_into() {
+    let partial = MotdPartial {
+        author: Some("you".to_string()),
+        ..Default::default()
+    };
+    let merged = partial.merge_into(motd());
+    assert_eq!(merged.author, "you");
+    assert_eq!(merged.message_text, "hi");
+    assert!(merged.enabled);
+
+    assert_eq!(MotdPartial::default().merge_into(motd()), motd());
+}
+
+#[test]
+fn try_complete() {
+    assert_eq!(MotdPartial::from(motd()).try_complete(), Ok(motd()));
+
+    let partial = MotdPartial {
+        enabled: None,
+        ..MotdPartial::from(motd())
+    };
+    assert_eq!(partial.clone().try_complete(), Err(partial));
+}
+
+#[test]
+fn serde_names_match_model() {
+    let partial = MotdPartial {
+        message_text: Some("hi".to_string()),
+        author: Some("me".to_string()),
+        enabled: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&partial).unwrap(),
+        json!({"message-text": "hi", "by": "me", "enabled": null})
+    );
+
+    let parsed: MotdPartial = serde_json::from_value(json!({"by": "you", "shown": false})).unwrap();
+    assert_eq!(parsed.author.as_deref(), Some("you"));
+    assert_eq!(parsed.enabled, Some(false));
+    assert_eq!(parsed.message_text, None);
+}
+
+#[test]
+fn named_partial() {
+    let fragment = Fragment {
+        text: Some("hi".to_string()),
+    };
+    assert_eq!(
+        fragment.try_complete(),
+        Ok(Banner {
+            text: "hi".to_string()
+        })
+    );
+}
+
+#[test]
+fn fields_need_not_be_clone() {
+    let partial = ConnectionPartial::from(Connection { handle: Handle(7) });
+    assert_eq!(partial.try_complete().unwrap().handle.0, 7);
+}
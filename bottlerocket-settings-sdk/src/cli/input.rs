@@ -0,0 +1,98 @@
+use std::convert::Infallible;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use snafu::{ensure, ResultExt};
+
+use crate::error::{self, Result};
+
+/// A JSON argument of a command. Large or sensitive values can be passed as `@path` to read them
+/// from a file, or as `-` to read them from stdin.
+///
+/// Arguments are only read once the command line has been parsed, so that failures to read them
+/// are reported like any other failed input. Arguments of commands which are deserialized, such as
+/// the fields of a request envelope, are already parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonArg<T = serde_json::Value> {
+    /// JSON given directly as the argument.
+    Inline(String),
+    /// `@path`: JSON read from a file.
+    File(PathBuf),
+    /// `-`: JSON read from stdin.
+    Stdin,
+    /// A value which has already been parsed.
+    Parsed(T),
+}
+
+impl<T> FromStr for JsonArg<T> {
+    type Err = Infallible;
+
+    fn from_str(arg: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match arg {
+            "-" => JsonArg::Stdin,
+            arg => match arg.strip_prefix('@') {
+                Some(path) => JsonArg::File(path.into()),
+                None => JsonArg::Inline(arg.to_string()),
+            },
+        })
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for JsonArg<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        T::deserialize(deserializer).map(JsonArg::Parsed)
+    }
+}
+
+impl<T: DeserializeOwned> JsonArg<T> {
+    /// Reads the argument as a single JSON document.
+    pub(crate) fn read(self, input: &mut Input) -> Result<T> {
+        self.read_with(input, |reader| {
+            serde_json::from_reader(reader).context(error::ParseCliJsonSnafu)
+        })
+    }
+}
+
+impl<T> JsonArg<T> {
+    /// Reads the argument with the given parser, unless it has already been parsed.
+    pub(crate) fn read_with(
+        self,
+        input: &mut Input,
+        parse: impl FnOnce(&mut dyn Read) -> Result<T>,
+    ) -> Result<T> {
+        match self {
+            JsonArg::Parsed(value) => Ok(value),
+            JsonArg::Inline(json) => parse(&mut json.as_bytes()),
+            JsonArg::File(path) => {
+                let file = std::fs::File::open(&path).context(error::ReadCliInputSnafu { path })?;
+                parse(&mut BufReader::new(file))
+            }
+            JsonArg::Stdin => parse(input.stdin()?),
+        }
+    }
+}
+
+/// The stdin of a single invocation of an extension, which only one argument may read.
+pub(crate) struct Input<'a> {
+    stdin: &'a mut dyn Read,
+    stdin_read: bool,
+}
+
+impl<'a> Input<'a> {
+    pub(crate) fn new(stdin: &'a mut dyn Read) -> Self {
+        Self {
+            stdin,
+            stdin_read: false,
+        }
+    }
+
+    /// Takes stdin, failing if it has already been taken during this invocation.
+    pub(crate) fn stdin(&mut self) -> Result<&mut dyn Read> {
+        ensure!(!self.stdin_read, error::StdinReusedSnafu);
+        self.stdin_read = true;
+        Ok(&mut *self.stdin)
+    }
+}
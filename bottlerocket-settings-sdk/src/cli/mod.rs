@@ -1,13 +1,19 @@
+pub mod input;
 pub mod proto1;
+pub mod proto2;
 
 pub use clap::{Args, Parser, Subcommand};
 
+/// The settings extension protocols which extensions built with this SDK can be invoked with, from
+/// oldest to newest.
+pub const SUPPORTED_PROTOCOLS: &[&str] = &["proto1", "proto2"];
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
     #[command(subcommand)]
-    pub protocol: Protocol,
+    pub command: Command,
 }
 
 impl Cli {
@@ -17,8 +23,59 @@ impl Cli {
     }
 }
 
+// The top-level commands of an extension: either a protocol's command set, or a command about the
+// extension itself. Both kinds are invoked directly as subcommands, e.g. `proto1 set` or
+// `describe`. These are not doc comments, since clap would use them as the extension's help.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(flatten)]
+    Protocol(Protocol),
+    #[command(flatten)]
+    Extension(ExtensionCommand),
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Protocol {
     /// Settings extension protocol 1
     Proto1(proto1::Protocol1),
+    /// Settings extension protocol 2
+    Proto2(proto2::Protocol2),
+}
+
+// Commands which are not part of any settings extension protocol.
+#[derive(Subcommand, Debug)]
+pub enum ExtensionCommand {
+    /// Report the protocols this extension supports, and select one to use
+    Negotiate(NegotiateCommand),
+    /// Answer newline-delimited JSON-RPC requests until shut down
+    Serve(ServeCommand),
+    /// Print the extension config file describing this extension's models
+    GenerateConfig(GenerateConfigCommand),
+    /// Print a JSON description of the versions, migrations and operations this extension supports
+    Describe,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateConfigCommand {
+    /// The name of the setting provided by this extension
+    #[arg(long)]
+    pub name: String,
+
+    /// The setting version which should be used by default
+    #[arg(long)]
+    pub default_version: String,
+}
+
+#[derive(Args, Debug)]
+pub struct NegotiateCommand {
+    /// Comma-separated protocols which the caller can use, in order of preference
+    #[arg(long, value_delimiter = ',')]
+    pub accept: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeCommand {
+    /// Listen on a Unix domain socket at this path, rather than reading requests from stdin
+    #[arg(long)]
+    pub socket: Option<std::path::PathBuf>,
 }
@@ -1,7 +1,11 @@
-use anyhow::Context;
 use clap::{Args, Subcommand};
+use serde::Deserialize;
+use snafu::ResultExt;
 
-use crate::error::Result;
+use super::input::{Input, JsonArg};
+use crate::error::{self, Result};
+use crate::migrate::BatchMigrationEntry;
+use crate::template::HelperArgs;
 
 #[derive(Args, Debug)]
 pub struct Protocol1 {
@@ -9,7 +13,11 @@ pub struct Protocol1 {
     pub command: Proto1Command,
 }
 
-#[derive(Subcommand, Debug)]
+/// The commands of proto1, which proto2 shares. Besides being parsed from arguments, a command can
+/// be read from stdin as a single JSON request, e.g.
+/// `{"command": "set", "setting-version": "v1", "value": {...}}`.
+#[derive(Subcommand, Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
 pub enum Proto1Command {
     /// Modify values owned by this setting
     Set(SetCommand),
@@ -19,65 +27,177 @@ pub enum Proto1Command {
     Validate(ValidateCommand),
     /// Migrate this setting from one given version to another
     Migrate(MigrateCommand),
+    /// Migrate many values of this setting to one version
+    MigrateBatch(MigrateBatchCommand),
+    /// Print the JSON Schema of one or all versions of this setting
+    Schema(SchemaCommand),
+    /// Invoke a template helper provided by this setting
+    Helper(HelperCommand),
+    /// Render a value of this setting with its version's template
+    Render(RenderCommand),
+    /// Read a single JSON request, naming the command and its arguments, from stdin
+    #[serde(skip)]
+    Request,
 }
 
-impl Proto1Command {}
+impl Proto1Command {
+    /// The name of the operation performed by the command.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Proto1Command::Set(_) => "set",
+            Proto1Command::Generate(_) => "generate",
+            Proto1Command::Validate(_) => "validate",
+            Proto1Command::Migrate(_) => "migrate",
+            Proto1Command::MigrateBatch(_) => "migrate-batch",
+            Proto1Command::Schema(_) => "schema",
+            Proto1Command::Helper(_) => "helper",
+            Proto1Command::Render(_) => "render",
+            Proto1Command::Request => "request",
+        }
+    }
+}
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct SetCommand {
     /// The version of the setting which should be used
     #[arg(long)]
     pub setting_version: String,
 
     /// The requested value to be set for the incoming setting
-    #[arg(long, value_parser = parse_json)]
-    pub value: serde_json::Value,
+    #[arg(long)]
+    pub value: JsonArg,
 
     /// The current value of this settings tree
-    #[arg(long, value_parser = parse_json)]
-    pub current_value: Option<serde_json::Value>,
+    #[arg(long)]
+    pub current_value: Option<JsonArg>,
+
+    /// A json value containing any settings needed to validate the new value. If omitted, only
+    /// the checks which do not depend on other settings are made
+    #[arg(long)]
+    pub validated_settings: Option<JsonArg>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct GenerateCommand {
     /// The version of the setting which should be used
     #[arg(long)]
     pub setting_version: String,
 
     /// A json value containing any partially generated data for this setting
-    #[arg(long, value_parser = parse_json)]
-    pub existing_partial: Option<serde_json::Value>,
+    #[arg(long)]
+    pub existing_partial: Option<JsonArg>,
 
     /// A json value containing any requested settings partials needed to generate this one
-    #[arg(long, value_parser = parse_json)]
-    pub required_settings: Option<serde_json::Value>,
+    #[arg(long)]
+    pub required_settings: Option<JsonArg>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct ValidateCommand {
     /// The version of the setting which should be used
     #[arg(long)]
     pub setting_version: String,
 
     /// A json value containing any partially generated data for this setting
-    #[arg(long, value_parser = parse_json)]
-    pub value: serde_json::Value,
+    #[arg(long)]
+    pub value: JsonArg,
 
     /// A json value containing any requested settings partials needed to generate this one
-    #[arg(long, value_parser = parse_json)]
-    pub required_settings: Option<serde_json::Value>,
+    #[arg(long)]
+    pub required_settings: Option<JsonArg>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct MigrateCommand {
-    #[arg(long, value_parser = parse_json)]
-    pub value: serde_json::Value,
+    #[arg(long)]
+    pub value: JsonArg,
     #[arg(long)]
     pub from_version: String,
     #[arg(long)]
     pub target_version: String,
 }
 
-fn parse_json(arg: &str) -> Result<serde_json::Value> {
-    serde_json::from_str(&arg).context("Failed to parse CLI input as JSON.")
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MigrateBatchCommand {
+    /// A json array, or newline-delimited json, of entries with a `value` and a `from-version`
+    #[arg(long)]
+    pub values: JsonArg<BatchMigrationEntries>,
+    #[arg(long)]
+    pub target_version: String,
+}
+
+/// The entries of a batch migration.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct BatchMigrationEntries(pub Vec<BatchMigrationEntry>);
+
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SchemaCommand {
+    /// The version of the setting to describe. If omitted, the schemas of all versions are printed
+    #[arg(long)]
+    pub setting_version: Option<String>,
+}
+
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HelperCommand {
+    /// The version of the setting whose helper should be used
+    #[arg(long)]
+    pub setting_version: String,
+
+    /// The name of the helper
+    #[arg(long)]
+    pub name: String,
+
+    /// A json object with the helper's positional `params` and named `hash` arguments
+    #[arg(long)]
+    #[serde(default)]
+    pub args: Option<JsonArg<HelperArgs>>,
+}
+
+#[derive(Args, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RenderCommand {
+    /// The version of the setting whose template should be used
+    #[arg(long)]
+    pub setting_version: String,
+
+    /// The stored value of the setting to render
+    #[arg(long)]
+    pub value: JsonArg,
+}
+
+impl BatchMigrationEntries {
+    /// Parses the entries of a batch migration, given either as a JSON array or as a stream of
+    /// newline-delimited JSON entries.
+    pub(crate) fn read(values: JsonArg<Self>, input: &mut Input) -> Result<Self> {
+        values.read_with(input, |reader| {
+            let mut entries = Vec::new();
+            for document in serde_json::Deserializer::from_reader(reader).into_iter() {
+                match document.context(error::ParseCliJsonSnafu)? {
+                    serde_json::Value::Array(items) => {
+                        for item in items {
+                            entries.push(
+                                serde_json::from_value(item).context(error::ParseCliJsonSnafu)?,
+                            );
+                        }
+                    }
+                    item => entries
+                        .push(serde_json::from_value(item).context(error::ParseCliJsonSnafu)?),
+                }
+            }
+            Ok(BatchMigrationEntries(entries))
+        })
+    }
+}
+
+/// Reads a single JSON document, such as a request, from stdin.
+pub(crate) fn read_stdin<T: serde::de::DeserializeOwned>(input: &mut Input) -> Result<T> {
+    JsonArg::Stdin.read(input)
 }
@@ -0,0 +1,14 @@
+use clap::Args;
+
+pub use super::proto1::{
+    GenerateCommand, HelperCommand, MigrateBatchCommand, MigrateCommand, Proto1Command,
+    RenderCommand, SchemaCommand, SetCommand, ValidateCommand,
+};
+
+// Protocol 2 accepts the same commands as protocol 1, but wraps each result in an envelope which
+// identifies the protocol and operation.
+#[derive(Args, Debug)]
+pub struct Protocol2 {
+    #[command(subcommand)]
+    pub command: Proto1Command,
+}
@@ -0,0 +1,146 @@
+//! Typed representation of the settings extension configuration file, e.g. `motd.toml`.
+//!
+//! The configuration file tells the settings orchestrator which versions of a setting an extension
+//! supports, and can be checked against the extension's models with
+//! `SettingsExtension::check_config`.
+//!
+//! Configs are validated against the format's schema whenever they are parsed or written. Problems
+//! found while parsing point at their line and column in the TOML source.
+mod validate;
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, ResultExt};
+
+use crate::error::{self, Result};
+
+pub use validate::{ConfigField, ConfigLocation, ConfigProblem};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ExtensionConfig {
+    pub extension: ExtensionInfo,
+    #[serde(default)]
+    pub generation: GenerationConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templating: BTreeMap<String, TemplatingConfig>,
+}
+
+/// The `[extension]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ExtensionInfo {
+    pub name: String,
+    pub supported_versions: Vec<String>,
+    pub default_version: String,
+}
+
+/// The `[generation]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GenerationConfig {
+    #[serde(default)]
+    pub required_settings: Vec<String>,
+}
+
+/// The `[validation]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ValidationConfig {
+    #[serde(default)]
+    pub validated_settings: Vec<String>,
+}
+
+/// A `[templating.<version>]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TemplatingConfig {
+    #[serde(default)]
+    pub helpers: Vec<String>,
+}
+
+impl ExtensionConfig {
+    /// Reads and validates an extension config from a TOML file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        ConfigParser::new().parse_path(path)
+    }
+
+    /// Parses and validates an extension config from a TOML string.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        ConfigParser::new().parse(contents)
+    }
+
+    /// Validates the config and renders it as TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        let problems = ConfigParser::new().validate(self);
+        ensure!(problems.is_empty(), error::ConfigInvalidSnafu { problems });
+        toml::to_string_pretty(self).context(error::ConfigSerializeSnafu)
+    }
+
+    /// Validates the config and writes it as TOML to the given file.
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_toml()?).context(error::ConfigWriteSnafu { path })
+    }
+}
+
+/// Parses and validates extension configs.
+///
+/// By default, helper names are only checked for formatting. Use `with_known_helpers` to also
+/// reject helpers which the extension does not provide.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigParser {
+    known_helpers: Option<HashSet<String>>,
+}
+
+impl ConfigParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the helpers which may be listed under `[templating.<version>]` tables.
+    pub fn with_known_helpers<I, S>(mut self, helpers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known_helpers = Some(helpers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Reads and validates an extension config from a TOML file.
+    pub fn parse_path<P: AsRef<Path>>(&self, path: P) -> Result<ExtensionConfig> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).context(error::ConfigReadSnafu { path })?;
+        self.parse(&contents)
+            .map_err(Box::new)
+            .context(error::ConfigFileSnafu { path })
+    }
+
+    /// Parses and validates an extension config from a TOML string.
+    pub fn parse(&self, contents: &str) -> Result<ExtensionConfig> {
+        // Parse twice: once to the public config, and once to a representation of the document
+        // which records where each value was found.
+        let config: ExtensionConfig = toml::from_str(contents).context(error::ConfigParseSnafu)?;
+        let spans = validate::SpannedConfig::parse(contents);
+
+        let problems = validate::validate(&config, self.known_helpers.as_ref(), |field| {
+            spans
+                .as_ref()
+                .and_then(|spans| spans.span_of(field))
+                .map(|span| ConfigLocation::from_offset(contents, span.start))
+        });
+        ensure!(problems.is_empty(), error::ConfigInvalidSnafu { problems });
+
+        Ok(config)
+    }
+
+    /// Validates an extension config against the schema, returning every problem found.
+    pub fn validate(&self, config: &ExtensionConfig) -> Vec<ConfigProblem> {
+        validate::validate(config, self.known_helpers.as_ref(), |_| None)
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use toml::Spanned;
+
+use super::ExtensionConfig;
+
+/// A value in an extension config which a `ConfigProblem` refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigField {
+    /// `extension.name`
+    Name,
+    /// `extension.supported-versions`
+    SupportedVersions,
+    /// An entry of `extension.supported-versions`, by index.
+    SupportedVersion(usize),
+    /// `extension.default-version`
+    DefaultVersion,
+    /// The `[templating.<version>]` table for the given version.
+    Templating(String),
+    /// An entry of `templating.<version>.helpers`, by index.
+    Helper(String, usize),
+}
+
+impl Display for ConfigField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigField::Name => write!(f, "extension.name"),
+            ConfigField::SupportedVersions => write!(f, "extension.supported-versions"),
+            ConfigField::SupportedVersion(ndx) => {
+                write!(f, "extension.supported-versions[{}]", ndx)
+            }
+            ConfigField::DefaultVersion => write!(f, "extension.default-version"),
+            ConfigField::Templating(version) => write!(f, "templating.{}", version),
+            ConfigField::Helper(version, ndx) => {
+                write!(f, "templating.{}.helpers[{}]", version, ndx)
+            }
+        }
+    }
+}
+
+/// A position in the TOML source of an extension config. Both fields are 1-indexed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ConfigLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ConfigLocation {
+    /// Converts a byte offset into the given source into a line and column.
+    pub(crate) fn from_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|ndx| ndx + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        Self { line, column }
+    }
+}
+
+/// A violation of the extension config schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigProblem {
+    pub field: ConfigField,
+    /// Where the offending value appears, if the config was parsed from TOML.
+    pub location: Option<ConfigLocation>,
+    pub message: String,
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(location) = self.location {
+            write!(f, "line {}, column {}: ", location.line, location.column)?;
+        }
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Checks a config against the schema. `locate` returns the position of a field in the TOML
+/// source, if it is known.
+pub(super) fn validate<F>(
+    config: &ExtensionConfig,
+    known_helpers: Option<&HashSet<String>>,
+    locate: F,
+) -> Vec<ConfigProblem>
+where
+    F: Fn(&ConfigField) -> Option<ConfigLocation>,
+{
+    let mut problems = Vec::new();
+    let mut problem = |field: ConfigField, message: String| {
+        let location = locate(&field);
+        problems.push(ConfigProblem {
+            field,
+            location,
+            message,
+        });
+    };
+
+    let info = &config.extension;
+    if !is_kebab_case(&info.name) {
+        problem(
+            ConfigField::Name,
+            format!("extension name '{}' must be kebab-case", info.name),
+        );
+    }
+
+    if info.supported_versions.is_empty() {
+        problem(
+            ConfigField::SupportedVersions,
+            "extension must support at least one version".to_string(),
+        );
+    }
+    let mut versions = HashSet::new();
+    for (ndx, version) in info.supported_versions.iter().enumerate() {
+        if !is_kebab_case(version) {
+            problem(
+                ConfigField::SupportedVersion(ndx),
+                format!("version '{}' must be kebab-case", version),
+            );
+        }
+        if !versions.insert(version.as_str()) {
+            problem(
+                ConfigField::SupportedVersion(ndx),
+                format!("version '{}' is listed more than once", version),
+            );
+        }
+    }
+
+    if !versions.contains(info.default_version.as_str()) {
+        problem(
+            ConfigField::DefaultVersion,
+            format!(
+                "default version '{}' is not a supported version",
+                info.default_version
+            ),
+        );
+    }
+
+    for (version, templating) in &config.templating {
+        if !versions.contains(version.as_str()) {
+            problem(
+                ConfigField::Templating(version.clone()),
+                format!(
+                    "templating is configured for unsupported version '{}'",
+                    version
+                ),
+            );
+        }
+
+        let mut helpers = HashSet::new();
+        for (ndx, helper) in templating.helpers.iter().enumerate() {
+            let field = || ConfigField::Helper(version.clone(), ndx);
+            if !is_kebab_case(helper) {
+                problem(field(), format!("helper '{}' must be kebab-case", helper));
+            }
+            if !helpers.insert(helper.as_str()) {
+                problem(
+                    field(),
+                    format!("helper '{}' is listed more than once", helper),
+                );
+            }
+            if known_helpers.is_some_and(|known| !known.contains(helper)) {
+                problem(field(), format!("helper '{}' is not known", helper));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Returns whether a name consists of lowercase alphanumeric words separated by single hyphens.
+fn is_kebab_case(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Mirror of `ExtensionConfig` which records the location of each value validated by `validate`.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(super) struct SpannedConfig {
+    extension: SpannedExtensionInfo,
+    #[serde(default)]
+    templating: BTreeMap<String, Spanned<SpannedTemplatingConfig>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SpannedExtensionInfo {
+    name: Spanned<String>,
+    supported_versions: Spanned<Vec<Spanned<String>>>,
+    default_version: Spanned<String>,
+}
+
+#[derive(Deserialize)]
+struct SpannedTemplatingConfig {
+    #[serde(default)]
+    helpers: Vec<Spanned<String>>,
+}
+
+impl SpannedConfig {
+    /// Parses the config's spans. Returns `None` if the source is not a valid config.
+    pub(super) fn parse(source: &str) -> Option<Self> {
+        toml::from_str(source).ok()
+    }
+
+    /// Returns the byte range of a field in the TOML source.
+    pub(super) fn span_of(&self, field: &ConfigField) -> Option<Range<usize>> {
+        match field {
+            ConfigField::Name => Some(self.extension.name.span()),
+            ConfigField::SupportedVersions => Some(self.extension.supported_versions.span()),
+            ConfigField::SupportedVersion(ndx) => self
+                .extension
+                .supported_versions
+                .get_ref()
+                .get(*ndx)
+                .map(Spanned::span),
+            ConfigField::DefaultVersion => Some(self.extension.default_version.span()),
+            ConfigField::Templating(version) => self.templating.get(version).map(Spanned::span),
+            ConfigField::Helper(version, ndx) => self
+                .templating
+                .get(version)
+                .and_then(|templating| templating.get_ref().helpers.get(*ndx))
+                .map(Spanned::span),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::ConfigParser;
+    use crate::error::Error;
+
+    const VALID: &str = r#"
+[extension]
+name = "motd"
+supported-versions = ["v1", "v2"]
+default-version = "v2"
+
+[generation]
+required-settings = ["hostname"]
+
+[validation]
+validated-settings = ["hostname"]
+
+[templating.v1]
+helpers = ["sample-helper"]
+"#;
+
+    fn problems_with(parser: ConfigParser, source: &str) -> Vec<ConfigProblem> {
+        match parser.parse(source) {
+            Err(Error::ConfigInvalid { problems }) => problems,
+            result => panic!("config was not rejected: {:?}", result),
+        }
+    }
+
+    /// Returns the field and message of each problem with a config.
+    fn problems(source: &str) -> Vec<(ConfigField, String)> {
+        problems_with(ConfigParser::new(), source)
+            .into_iter()
+            .map(|problem| (problem.field, problem.message))
+            .collect()
+    }
+
+    fn extension(name: &str, supported_versions: &str, default_version: &str) -> String {
+        format!(
+            "[extension]\nname = \"{}\"\nsupported-versions = {}\ndefault-version = \"{}\"\n",
+            name, supported_versions, default_version
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = ConfigParser::new().parse(VALID).unwrap();
+        assert_eq!(config.extension.supported_versions, ["v1", "v2"]);
+        assert_eq!(config.templating["v1"].helpers, ["sample-helper"]);
+
+        let written = config.to_toml().unwrap();
+        assert_eq!(ConfigParser::new().parse(&written).unwrap(), config);
+    }
+
+    #[test]
+    fn name_not_kebab_case() {
+        assert_eq!(
+            problems(&extension("Motd_Ext", r#"["v1"]"#, "v1")),
+            [(
+                ConfigField::Name,
+                "extension name 'Motd_Ext' must be kebab-case".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn no_supported_versions() {
+        assert_eq!(
+            problems(&extension("motd", "[]", "v1")),
+            [
+                (
+                    ConfigField::SupportedVersions,
+                    "extension must support at least one version".to_string()
+                ),
+                (
+                    ConfigField::DefaultVersion,
+                    "default version 'v1' is not a supported version".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn version_not_kebab_case() {
+        assert_eq!(
+            problems(&extension("motd", r#"["v1", "V2"]"#, "v1")),
+            [(
+                ConfigField::SupportedVersion(1),
+                "version 'V2' must be kebab-case".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn duplicate_version() {
+        assert_eq!(
+            problems(&extension("motd", r#"["v1", "v2", "v1"]"#, "v1")),
+            [(
+                ConfigField::SupportedVersion(2),
+                "version 'v1' is listed more than once".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn default_version_not_supported() {
+        assert_eq!(
+            problems(&extension("motd", r#"["v1"]"#, "v2")),
+            [(
+                ConfigField::DefaultVersion,
+                "default version 'v2' is not a supported version".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn templating_unsupported_version() {
+        let source = extension("motd", r#"["v1"]"#, "v1") + "[templating.v2]\nhelpers = []\n";
+        assert_eq!(
+            problems(&source),
+            [(
+                ConfigField::Templating("v2".to_string()),
+                "templating is configured for unsupported version 'v2'".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn invalid_helpers() {
+        let source = extension("motd", r#"["v1"]"#, "v1")
+            + "[templating.v1]\nhelpers = [\"sample-helper\", \"Helper\", \"sample-helper\"]\n";
+        assert_eq!(
+            problems(&source),
+            [
+                (
+                    ConfigField::Helper("v1".to_string(), 1),
+                    "helper 'Helper' must be kebab-case".to_string()
+                ),
+                (
+                    ConfigField::Helper("v1".to_string(), 2),
+                    "helper 'sample-helper' is listed more than once".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_helper() {
+        let parser = ConfigParser::new().with_known_helpers(["sample-helper"]);
+        assert!(parser.parse(VALID).is_ok());
+
+        let source =
+            extension("motd", r#"["v1"]"#, "v1") + "[templating.v1]\nhelpers = [\"other\"]\n";
+        let problems = problems_with(parser, &source);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field, ConfigField::Helper("v1".to_string(), 0));
+        assert_eq!(problems[0].message, "helper 'other' is not known");
+    }
+
+    #[test]
+    fn locates_problems() {
+        let source = extension("motd", "[\n    \"v1\",\n    \"V2\",\n]", "v1");
+        let problems = problems_with(ConfigParser::new(), &source);
+        assert_eq!(
+            problems[0].location,
+            Some(ConfigLocation { line: 5, column: 5 })
+        );
+        assert_eq!(
+            problems[0].to_string(),
+            "line 5, column 5: extension.supported-versions[1]: version 'V2' must be kebab-case"
+        );
+
+        let problems = problems_with(ConfigParser::new(), &extension("my_motd", "[\"v1\"]", "v1"));
+        assert_eq!(
+            problems[0].location,
+            Some(ConfigLocation { line: 2, column: 8 })
+        );
+    }
+
+    #[test]
+    fn validating_without_source() {
+        let mut config = ConfigParser::new().parse(VALID).unwrap();
+        config.extension.name = "Motd".to_string();
+        let problems = ConfigParser::new().validate(&config);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].location, None);
+        assert!(matches!(
+            config.to_toml(),
+            Err(Error::ConfigInvalid { problems }) if problems.len() == 1
+        ));
+    }
+}
@@ -1,6 +1,412 @@
+use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 
-#[derive(Debug, Snafu, Clone)]
-pub enum Error {}
+use crate::config::ConfigProblem;
+use crate::extension::ModelDiagnostic;
+use crate::validation::ValidationReport;
 
-pub(crate) type Result<T> = anyhow::Result<T>;
+/// Errors that can be returned by the settings SDK while servicing an extension request.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Requested model version '{}' not found", version))]
+    ModelVersionNotFound { version: String },
+
+    #[snafu(display(
+        "Could not find a defined migration from '{}' to '{}'",
+        starting_version,
+        target_version
+    ))]
+    MigrationRouteNotFound {
+        starting_version: String,
+        target_version: String,
+    },
+
+    #[snafu(display(
+        "Found {} equally short migration routes from '{}' to '{}'",
+        routes,
+        starting_version,
+        target_version
+    ))]
+    AmbiguousMigrationRoute {
+        starting_version: String,
+        target_version: String,
+        routes: usize,
+    },
+
+    #[snafu(display(
+        "Failed to perform sub-migration from '{}' to '{}' as part of migration from '{}' to '{}'",
+        from_version,
+        to_version,
+        starting_version,
+        target_version
+    ))]
+    MigrationFailed {
+        from_version: String,
+        to_version: String,
+        starting_version: String,
+        target_version: String,
+        #[snafu(source(from(Error, Box::new)))]
+        source: Box<Error>,
+    },
+
+    #[snafu(display("Failed to deserialize {} for setting version '{}'", input, version))]
+    DeserializeInput {
+        version: String,
+        input: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("Failed to serialize {} for setting version '{}'", output, version))]
+    SerializeOutput {
+        version: String,
+        output: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display(
+        "Settings model '{}' requires settings for '{}' which were not provided: {}",
+        version,
+        operation,
+        missing.join(", ")
+    ))]
+    MissingDependentSettings {
+        version: String,
+        operation: &'static str,
+        missing: Vec<String>,
+    },
+
+    #[snafu(display(
+        "Settings model '{}' received a malformed dependent setting '{}' for '{}'",
+        version,
+        path,
+        operation
+    ))]
+    MalformedDependentSettings {
+        version: String,
+        operation: &'static str,
+        path: String,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("Settings model '{}' failed during '{}'", version, operation))]
+    ModelCallbackFailed {
+        version: String,
+        operation: &'static str,
+        #[snafu(source(from(anyhow::Error, Into::into)))]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+
+    #[snafu(display(
+        "Registered settings models are inconsistent: {}",
+        diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    ))]
+    InconsistentModels { diagnostics: Vec<ModelDiagnostic> },
+
+    #[snafu(display("Failed to read extension config '{}'", path.display()))]
+    ConfigRead {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to parse extension config"))]
+    ConfigParse { source: toml::de::Error },
+
+    #[snafu(display(
+        "Invalid extension config: {}",
+        problems
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    ))]
+    ConfigInvalid { problems: Vec<ConfigProblem> },
+
+    #[snafu(display("Invalid extension config file '{}'", path.display()))]
+    ConfigFile {
+        path: std::path::PathBuf,
+        source: Box<Error>,
+    },
+
+    #[snafu(display("Failed to serialize extension config"))]
+    ConfigSerialize { source: toml::ser::Error },
+
+    #[snafu(display("Failed to write extension config '{}'", path.display()))]
+    ConfigWrite {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display(
+        "Value for settings model '{}' failed validation: {}",
+        version,
+        report
+            .errors()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    ))]
+    ValidationFailed {
+        version: String,
+        report: ValidationReport,
+    },
+
+    #[snafu(display("Settings model '{}' does not publish a JSON Schema", version))]
+    SchemaNotAvailable { version: String },
+
+    #[snafu(display(
+        "None of the requested protocols are supported: {}",
+        accepted.join(", ")
+    ))]
+    NoCommonProtocol { accepted: Vec<String> },
+
+    #[snafu(display("Failed to read CLI input from '{}'", path.display()))]
+    ReadCliInput {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Only one CLI input may be read from stdin"))]
+    StdinReused,
+
+    #[snafu(display(
+        "No template helper named '{}' is registered for setting version '{}'",
+        name,
+        version
+    ))]
+    HelperNotFound { version: String, name: String },
+
+    #[snafu(display("Template helper '{}' for setting version '{}' failed", name, version))]
+    HelperFailed {
+        version: String,
+        name: String,
+        #[snafu(source(from(anyhow::Error, Into::into)))]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+
+    #[snafu(display("Settings model '{}' does not provide a template", version))]
+    TemplateNotAvailable { version: String },
+
+    #[snafu(display(
+        "Template for setting version '{}' is invalid{}: {}",
+        version,
+        location(line, column),
+        reason
+    ))]
+    InvalidTemplate {
+        version: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        reason: String,
+    },
+
+    #[snafu(display(
+        "Failed to render template for setting version '{}'{}: {}",
+        version,
+        location(line, column),
+        reason
+    ))]
+    RenderTemplate {
+        version: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        reason: String,
+    },
+
+    #[snafu(display("Failed to bind server socket '{}'", path.display()))]
+    BindSocket {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Server connection failed"))]
+    ServerIo { source: std::io::Error },
+
+    #[snafu(display("Invalid extension arguments: {}", message))]
+    ParseArgs { message: String },
+
+    #[snafu(display("Failed to write command output"))]
+    WriteOutput { source: std::io::Error },
+
+    #[snafu(display("Failed to parse CLI input as JSON"))]
+    ParseCliJson { source: serde_json::Error },
+
+    #[snafu(display("Failed to write settings result to JSON"))]
+    WriteResult { source: serde_json::Error },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Describes where in a template an error occurred, if known.
+fn location(line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(" at line {}, column {}", line, column),
+        (Some(line), None) => format!(" at line {}", line),
+        _ => String::new(),
+    }
+}
+
+impl Error {
+    /// Returns the broad category of this error, which determines the extension's exit code.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::ModelVersionNotFound { .. } => ErrorKind::UnknownVersion,
+            Error::MigrationRouteNotFound { .. }
+            | Error::AmbiguousMigrationRoute { .. }
+            | Error::MigrationFailed { .. } => ErrorKind::MigrationFailure,
+            Error::DeserializeInput { .. }
+            | Error::MissingDependentSettings { .. }
+            | Error::MalformedDependentSettings { .. }
+            | Error::SchemaNotAvailable { .. }
+            | Error::HelperNotFound { .. }
+            | Error::TemplateNotAvailable { .. }
+            | Error::NoCommonProtocol { .. }
+            | Error::ReadCliInput { .. }
+            | Error::StdinReused
+            | Error::ParseArgs { .. }
+            | Error::ParseCliJson { .. } => ErrorKind::BadInput,
+            Error::ModelCallbackFailed { .. }
+            | Error::HelperFailed { .. }
+            | Error::RenderTemplate { .. } => ErrorKind::ModelCallbackFailure,
+            Error::ValidationFailed { .. } => ErrorKind::ValidationFailure,
+            Error::SerializeOutput { .. }
+            | Error::InvalidTemplate { .. }
+            | Error::BindSocket { .. }
+            | Error::ServerIo { .. }
+            | Error::WriteOutput { .. }
+            | Error::WriteResult { .. }
+            | Error::InconsistentModels { .. }
+            | Error::ConfigRead { .. }
+            | Error::ConfigParse { .. }
+            | Error::ConfigInvalid { .. }
+            | Error::ConfigFile { .. }
+            | Error::ConfigSerialize { .. }
+            | Error::ConfigWrite { .. } => ErrorKind::Internal,
+        }
+    }
+
+    /// Returns the setting version which the error relates to, if any.
+    pub fn setting_version(&self) -> Option<&str> {
+        match self {
+            Error::ModelVersionNotFound { version }
+            | Error::DeserializeInput { version, .. }
+            | Error::MissingDependentSettings { version, .. }
+            | Error::MalformedDependentSettings { version, .. }
+            | Error::SerializeOutput { version, .. }
+            | Error::ModelCallbackFailed { version, .. }
+            | Error::ValidationFailed { version, .. }
+            | Error::SchemaNotAvailable { version }
+            | Error::HelperNotFound { version, .. }
+            | Error::HelperFailed { version, .. }
+            | Error::TemplateNotAvailable { version }
+            | Error::InvalidTemplate { version, .. }
+            | Error::RenderTemplate { version, .. } => Some(version),
+            Error::MigrationRouteNotFound {
+                starting_version, ..
+            }
+            | Error::AmbiguousMigrationRoute {
+                starting_version, ..
+            } => Some(starting_version),
+            Error::MigrationFailed { from_version, .. } => Some(from_version),
+            Error::ParseCliJson { .. }
+            | Error::ParseArgs { .. }
+            | Error::WriteOutput { .. }
+            | Error::NoCommonProtocol { .. }
+            | Error::ReadCliInput { .. }
+            | Error::StdinReused
+            | Error::BindSocket { .. }
+            | Error::ServerIo { .. }
+            | Error::WriteResult { .. }
+            | Error::InconsistentModels { .. }
+            | Error::ConfigRead { .. }
+            | Error::ConfigParse { .. }
+            | Error::ConfigInvalid { .. }
+            | Error::ConfigFile { .. }
+            | Error::ConfigSerialize { .. }
+            | Error::ConfigWrite { .. } => None,
+        }
+    }
+}
+
+/// Categories of extension failures.
+///
+/// Each category is reported with a distinct process exit code:
+///
+/// | Kind                     | Exit code |
+/// |--------------------------|-----------|
+/// | `internal`               | 1         |
+/// | `bad-input`              | 2         |
+/// | `unknown-version`        | 3         |
+/// | `migration-failure`      | 4         |
+/// | `model-callback-failure` | 5         |
+/// | `validation-failure`     | 6         |
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// The extension itself failed, e.g. it could not serialize its own output.
+    Internal,
+    /// The input provided to the extension could not be parsed.
+    BadInput,
+    /// The requested setting version is not registered with the extension.
+    UnknownVersion,
+    /// No migration route exists, or a step of the migration failed.
+    MigrationFailure,
+    /// A settings model returned an error from one of its callbacks, or a template helper or
+    /// template failed.
+    ModelCallbackFailure,
+    /// A value was rejected by its settings model's validation.
+    ValidationFailure,
+}
+
+impl ErrorKind {
+    /// The process exit code used when an extension fails with this kind of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Internal => 1,
+            ErrorKind::BadInput => 2,
+            ErrorKind::UnknownVersion => 3,
+            ErrorKind::MigrationFailure => 4,
+            ErrorKind::ModelCallbackFailure => 5,
+            ErrorKind::ValidationFailure => 6,
+        }
+    }
+}
+
+/// Machine-readable description of an error, written to stderr when an extension command fails.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub causes: Vec<String>,
+    pub setting_version: Option<String>,
+    /// The validation report of a value which was rejected by its settings model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationReport>,
+}
+
+impl From<&Error> for ErrorReport {
+    fn from(error: &Error) -> Self {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+
+        Self {
+            kind: error.kind(),
+            message: error.to_string(),
+            causes,
+            setting_version: error.setting_version().map(str::to_string),
+            validation: match error {
+                Error::ValidationFailed { report, .. } => Some(report.clone()),
+                _ => None,
+            },
+        }
+    }
+}
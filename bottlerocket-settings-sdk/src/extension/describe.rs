@@ -0,0 +1,90 @@
+use serde::Serialize;
+
+use crate::cli::SUPPORTED_PROTOCOLS;
+use crate::migrate::MigrationEdge;
+use crate::model::{Model, SettingDependency};
+use crate::SettingsExtension;
+
+/// A machine-readable description of an extension's capabilities, printed by the `describe`
+/// command so that orchestrators can discover them without invoking each operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExtensionDescription {
+    /// The settings extension protocols which the extension can be invoked with.
+    pub protocols: Vec<&'static str>,
+    /// The registered model versions, in the order they were registered.
+    pub versions: Vec<VersionDescription>,
+    /// Every migration between registered versions.
+    pub migrations: Vec<MigrationEdge>,
+}
+
+/// The capabilities of a single model version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VersionDescription {
+    pub version: &'static str,
+    pub migrates_forward_to: Option<&'static str>,
+    pub migrates_backward_to: Option<&'static str>,
+    /// The versions which this version can be migrated to in a single step.
+    pub neighbours: Vec<&'static str>,
+    pub required_settings: &'static [SettingDependency],
+    pub validated_settings: &'static [SettingDependency],
+    /// The template helpers registered for this version.
+    pub helpers: Vec<String>,
+    /// The operations which this version supports, including optional ones such as `schema` and
+    /// `render`.
+    pub operations: Vec<&'static str>,
+}
+
+/// Operations which every model version supports.
+const REQUIRED_OPERATIONS: &[&str] = &["set", "generate", "validate", "migrate", "migrate-batch"];
+
+pub(super) fn describe(extension: &SettingsExtension) -> ExtensionDescription {
+    let versions = extension
+        .versions
+        .iter()
+        .filter_map(|version| extension.model(version))
+        .map(|model| describe_version(extension, model))
+        .collect();
+
+    ExtensionDescription {
+        protocols: SUPPORTED_PROTOCOLS.to_vec(),
+        versions,
+        migrations: extension.migrations().edges().copied().collect(),
+    }
+}
+
+fn describe_version(extension: &SettingsExtension, model: &dyn Model) -> VersionDescription {
+    let version = model.get_version();
+
+    let mut operations = REQUIRED_OPERATIONS.to_vec();
+    if model.json_schema().is_some() {
+        operations.push("schema");
+    }
+    let helpers: Vec<String> = extension
+        .helpers(version)
+        .map(|helper| helper.name().to_string())
+        .collect();
+    if !helpers.is_empty() {
+        operations.push("helper");
+    }
+    if extension.has_template(version) {
+        operations.push("render");
+    }
+
+    VersionDescription {
+        version,
+        migrates_forward_to: model.migrates_forward_to(),
+        migrates_backward_to: model.migrates_backward_to(),
+        neighbours: extension
+            .migrations()
+            .edges_from(version)
+            .iter()
+            .map(|edge| edge.to)
+            .collect(),
+        required_settings: model.required_settings(),
+        validated_settings: model.validated_settings(),
+        helpers,
+        operations,
+    }
+}
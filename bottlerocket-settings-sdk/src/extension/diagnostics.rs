@@ -0,0 +1,339 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::config::ExtensionConfig;
+use crate::migrate::{MigrationDirection, MigrationGraph};
+use crate::model::Model;
+use crate::SettingsExtension;
+
+/// An inconsistency in the set of models registered with a `SettingsExtension`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "diagnostic", rename_all = "kebab-case")]
+pub enum ModelDiagnostic {
+    /// More than one model was registered with the same version string. Only the last of these
+    /// models is used.
+    DuplicateVersion { version: String },
+    /// A model names a migration target whose version is not registered.
+    DanglingMigrationTarget {
+        version: String,
+        direction: MigrationDirection,
+        target: String,
+    },
+    /// A migration is registered from a version which is not registered.
+    DanglingMigrationSource {
+        version: String,
+        direction: MigrationDirection,
+        target: String,
+    },
+    /// A model's migration target does not migrate back to the model in the opposite direction.
+    AsymmetricMigration {
+        version: String,
+        direction: MigrationDirection,
+        target: String,
+        target_migrates_to: Option<String>,
+    },
+    /// A version cannot be migrated to from some of the other registered versions.
+    UnreachableVersion {
+        version: String,
+        unreachable_from: Vec<String>,
+    },
+    /// The extension config lists a supported version which has no registered model.
+    ConfigVersionNotRegistered { version: String },
+    /// A registered model's version is missing from the extension config's supported versions.
+    VersionMissingFromConfig { version: String },
+    /// The extension config's default version is not one of its registered, supported versions.
+    DefaultVersionNotSupported { version: String },
+    /// The extension config lists a templating helper which is not registered for its version.
+    HelperNotRegistered { version: String, helper: String },
+    /// The template registered for a version cannot be parsed.
+    InvalidTemplate {
+        version: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        reason: String,
+    },
+}
+
+/// How seriously a `ModelDiagnostic` should be taken.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticSeverity {
+    /// The model set may be intentionally shaped this way, such as a branching version line.
+    Warning,
+    /// The extension cannot service every request for its registered versions.
+    Error,
+}
+
+impl ModelDiagnostic {
+    pub fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            ModelDiagnostic::AsymmetricMigration { .. } => DiagnosticSeverity::Warning,
+            ModelDiagnostic::DuplicateVersion { .. }
+            | ModelDiagnostic::DanglingMigrationTarget { .. }
+            | ModelDiagnostic::DanglingMigrationSource { .. }
+            | ModelDiagnostic::UnreachableVersion { .. }
+            | ModelDiagnostic::ConfigVersionNotRegistered { .. }
+            | ModelDiagnostic::VersionMissingFromConfig { .. }
+            | ModelDiagnostic::DefaultVersionNotSupported { .. }
+            | ModelDiagnostic::HelperNotRegistered { .. }
+            | ModelDiagnostic::InvalidTemplate { .. } => DiagnosticSeverity::Error,
+        }
+    }
+}
+
+impl Display for ModelDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelDiagnostic::DuplicateVersion { version } => {
+                write!(f, "version '{}' is registered more than once", version)
+            }
+            ModelDiagnostic::DanglingMigrationTarget {
+                version,
+                direction,
+                target,
+            } => write!(
+                f,
+                "version '{}' has {} migration target '{}', which is not registered",
+                version, direction, target
+            ),
+            ModelDiagnostic::DanglingMigrationSource {
+                version,
+                direction,
+                target,
+            } => write!(
+                f,
+                "a {} migration to '{}' starts from version '{}', which is not registered",
+                direction, target, version
+            ),
+            ModelDiagnostic::AsymmetricMigration {
+                version,
+                direction,
+                target,
+                target_migrates_to,
+            } => write!(
+                f,
+                "version '{}' has {} migration target '{}', which migrates back to '{}'",
+                version,
+                direction,
+                target,
+                target_migrates_to.as_deref().unwrap_or("nothing")
+            ),
+            ModelDiagnostic::UnreachableVersion {
+                version,
+                unreachable_from,
+            } => write!(
+                f,
+                "version '{}' cannot be reached from: {}",
+                version,
+                unreachable_from.join(", ")
+            ),
+            ModelDiagnostic::ConfigVersionNotRegistered { version } => write!(
+                f,
+                "version '{}' is supported by the extension config, but has no registered model",
+                version
+            ),
+            ModelDiagnostic::VersionMissingFromConfig { version } => write!(
+                f,
+                "version '{}' is registered, but not supported by the extension config",
+                version
+            ),
+            ModelDiagnostic::DefaultVersionNotSupported { version } => write!(
+                f,
+                "default version '{}' is not a registered, supported version",
+                version
+            ),
+            ModelDiagnostic::HelperNotRegistered { version, helper } => write!(
+                f,
+                "helper '{}' is listed for version '{}' by the extension config, but is not \
+                 registered",
+                helper, version
+            ),
+            ModelDiagnostic::InvalidTemplate {
+                version,
+                line,
+                column,
+                reason,
+            } => {
+                write!(f, "the template for version '{}' is invalid", version)?;
+                if let (Some(line), Some(column)) = (line, column) {
+                    write!(f, " at line {}, column {}", line, column)?;
+                }
+                write!(f, ": {}", reason)
+            }
+        }
+    }
+}
+
+/// Looks up a model's migration target in one direction.
+type NeighbourFn = fn(&dyn Model) -> Option<&'static str>;
+
+/// Checks the models and migrations registered with an extension for inconsistencies.
+pub(crate) fn check_models(extension: &SettingsExtension) -> Vec<ModelDiagnostic> {
+    let mut diagnostics: Vec<_> = extension
+        .duplicate_versions
+        .iter()
+        .map(|version| ModelDiagnostic::DuplicateVersion {
+            version: version.to_string(),
+        })
+        .collect();
+
+    for model in extension.models.values() {
+        let version = model.get_version();
+        // Each migration target should migrate back to this model in the opposite direction.
+        let neighbours: [(_, _, NeighbourFn); 2] = [
+            (
+                MigrationDirection::Forward,
+                model.migrates_forward_to(),
+                |target| target.migrates_backward_to(),
+            ),
+            (
+                MigrationDirection::Backward,
+                model.migrates_backward_to(),
+                |target| target.migrates_forward_to(),
+            ),
+        ];
+        for (direction, target, migrates_back_to) in neighbours {
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+            let target_migrates_to = match extension.model(target) {
+                Some(target_model) => migrates_back_to(target_model),
+                None => {
+                    diagnostics.push(ModelDiagnostic::DanglingMigrationTarget {
+                        version: version.to_string(),
+                        direction,
+                        target: target.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if target_migrates_to != Some(version) {
+                diagnostics.push(ModelDiagnostic::AsymmetricMigration {
+                    version: version.to_string(),
+                    direction,
+                    target: target.to_string(),
+                    target_migrates_to: target_migrates_to.map(str::to_string),
+                });
+            }
+        }
+    }
+
+    for edge in extension.migrations.edges() {
+        if extension.model(edge.from).is_none() {
+            diagnostics.push(ModelDiagnostic::DanglingMigrationSource {
+                version: edge.from.to_string(),
+                direction: edge.direction,
+                target: edge.to.to_string(),
+            });
+        }
+        if extension.model(edge.to).is_none() {
+            diagnostics.push(ModelDiagnostic::DanglingMigrationTarget {
+                version: edge.from.to_string(),
+                direction: edge.direction,
+                target: edge.to.to_string(),
+            });
+        }
+    }
+
+    let versions: BTreeSet<&str> = extension.models.keys().map(String::as_str).collect();
+    let reachable: HashMap<&str, HashSet<&str>> = versions
+        .iter()
+        .map(|version| (*version, reachable_from(&extension.migrations, version)))
+        .collect();
+    for version in &versions {
+        let unreachable_from: Vec<String> = versions
+            .iter()
+            .filter(|from| !reachable[*from].contains(version))
+            .map(|from| from.to_string())
+            .collect();
+        if !unreachable_from.is_empty() {
+            diagnostics.push(ModelDiagnostic::UnreachableVersion {
+                version: version.to_string(),
+                unreachable_from,
+            });
+        }
+    }
+
+    #[cfg(feature = "handlebars")]
+    for (version, template) in &extension.templates {
+        if let Err(e) = handlebars::Template::compile(template) {
+            let (line, column) = e.pos().unzip();
+            diagnostics.push(ModelDiagnostic::InvalidTemplate {
+                version: version.to_string(),
+                line,
+                column,
+                reason: e.reason().to_string(),
+            });
+        }
+    }
+
+    diagnostics.sort();
+    diagnostics
+}
+
+/// Checks the versions listed in an extension config against the extension's registered models.
+pub(crate) fn check_config(
+    extension: &SettingsExtension,
+    config: &ExtensionConfig,
+) -> Vec<ModelDiagnostic> {
+    let supported: BTreeSet<&str> = config
+        .extension
+        .supported_versions
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let registered: BTreeSet<&str> = extension.models.keys().map(String::as_str).collect();
+
+    let mut diagnostics: Vec<_> = supported
+        .difference(&registered)
+        .map(|version| ModelDiagnostic::ConfigVersionNotRegistered {
+            version: version.to_string(),
+        })
+        .chain(registered.difference(&supported).map(|version| {
+            ModelDiagnostic::VersionMissingFromConfig {
+                version: version.to_string(),
+            }
+        }))
+        .collect();
+
+    let default_version = config.extension.default_version.as_str();
+    if !(supported.contains(default_version) && registered.contains(default_version)) {
+        diagnostics.push(ModelDiagnostic::DefaultVersionNotSupported {
+            version: default_version.to_string(),
+        });
+    }
+
+    for (version, templating) in &config.templating {
+        for helper in &templating.helpers {
+            let registered = extension
+                .helpers
+                .get(version.as_str())
+                .is_some_and(|helpers| helpers.contains_key(helper));
+            if !registered {
+                diagnostics.push(ModelDiagnostic::HelperNotRegistered {
+                    version: version.clone(),
+                    helper: helper.clone(),
+                });
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Returns every version which can be migrated to from the given version, including itself.
+fn reachable_from<'a>(migrations: &'a MigrationGraph, version: &'a str) -> HashSet<&'a str> {
+    let mut reachable = HashSet::from([version]);
+    let mut queue = VecDeque::from([version]);
+    while let Some(current) = queue.pop_front() {
+        for edge in migrations.edges_from(current) {
+            if reachable.insert(edge.to) {
+                queue.push_back(edge.to);
+            }
+        }
+    }
+    reachable
+}
@@ -1,40 +1,421 @@
+mod describe;
+mod diagnostics;
+mod negotiate;
+mod operations;
+mod output;
 pub mod proto1;
+pub mod proto2;
+mod server;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::path::Path;
 
-use anyhow::Context;
+use clap::Parser;
+use snafu::{ensure, OptionExt, ResultExt};
 
-use crate::cli;
-use crate::error::Result;
+pub use describe::{ExtensionDescription, VersionDescription};
+pub use diagnostics::{DiagnosticSeverity, ModelDiagnostic};
+pub use negotiate::ProtocolNegotiation;
+pub use output::Output;
+
+use crate::cli::{self, input::Input};
+use crate::config::{
+    ConfigParser, ExtensionConfig, ExtensionInfo, GenerationConfig, TemplatingConfig,
+    ValidationConfig,
+};
+use crate::error::{self, ErrorReport, Result};
+use crate::migrate::{
+    BatchMigrationEntry, BatchMigrationResult, GraphMigrator, MigrationDirection, MigrationEdge,
+    MigrationGraph, MigrationRoute, Migrator, RegisteredMigration,
+};
 use crate::model::Model;
+use crate::template::{HelperArgs, TemplateHelper};
+use crate::SettingsModel;
 
 #[derive(Debug)]
 pub struct SettingsExtension {
     pub(crate) models: HashMap<String, Box<dyn Model>>,
+    pub(crate) migrations: MigrationGraph,
+    pub(crate) migrator: Box<dyn Migrator>,
+    pub(crate) registered_migrations: HashMap<(&'static str, &'static str), RegisteredMigration>,
+    pub(crate) duplicate_versions: Vec<&'static str>,
+    /// Registered versions, in the order they were first registered.
+    pub(crate) versions: Vec<&'static str>,
+    /// Template helpers, by model version and helper name.
+    pub(crate) helpers: BTreeMap<&'static str, BTreeMap<String, TemplateHelper>>,
+    /// Handlebars templates, by model version. Only populated with the `handlebars` feature.
+    pub(crate) templates: BTreeMap<&'static str, String>,
 }
 
 impl SettingsExtension {
     pub fn with_models(models: Vec<Box<dyn Model>>) -> Self {
-        let models = models
+        let mut duplicate_versions = Vec::new();
+        let mut versions = Vec::new();
+        let mut model_map = HashMap::new();
+        for model in models {
+            let version = model.get_version();
+            if model_map.insert(version.to_string(), model).is_some() {
+                duplicate_versions.push(version);
+            } else {
+                versions.push(version);
+            }
+        }
+        let migrations = MigrationGraph::from_models(&model_map);
+        Self {
+            models: model_map,
+            migrations,
+            migrator: Box::new(GraphMigrator),
+            registered_migrations: HashMap::new(),
+            duplicate_versions,
+            versions,
+            helpers: BTreeMap::new(),
+            templates: BTreeMap::new(),
+        }
+    }
+
+    /// Creates an extension from the given models, failing if `model_diagnostics` reports any
+    /// errors for them.
+    ///
+    /// Extensions which rely on `register_migration` to connect their models should instead call
+    /// `validated` once their migrations are registered.
+    pub fn try_with_models(models: Vec<Box<dyn Model>>) -> Result<Self> {
+        Self::with_models(models).validated()
+    }
+
+    /// Checks the registered models and migrations for inconsistencies, such as duplicate versions
+    /// or versions which cannot be migrated to.
+    pub fn model_diagnostics(&self) -> Vec<ModelDiagnostic> {
+        diagnostics::check_models(self)
+    }
+
+    /// Checks that the versions supported by an extension config match the registered models, that
+    /// the config's default version is one of them, and that its templating helpers are registered.
+    pub fn check_config(&self, config: &ExtensionConfig) -> Result<()> {
+        let diagnostics = diagnostics::check_config(self, config);
+        ensure!(
+            diagnostics.is_empty(),
+            error::InconsistentModelsSnafu { diagnostics }
+        );
+        Ok(())
+    }
+
+    /// Returns the extension unchanged if `model_diagnostics` reports no errors, allowing an
+    /// extension to refuse to start with an inconsistent set of models.
+    pub fn validated(self) -> Result<Self> {
+        let diagnostics: Vec<_> = self
+            .model_diagnostics()
             .into_iter()
-            .map(|model| (model.get_version().to_string(), model))
+            .filter(|d| d.severity() == DiagnosticSeverity::Error)
             .collect();
-        Self { models }
+        ensure!(
+            diagnostics.is_empty(),
+            error::InconsistentModelsSnafu { diagnostics }
+        );
+        Ok(self)
+    }
+
+    /// Registers a migration directly between two models, such as a migration which skips
+    /// intermediate versions.
+    ///
+    /// The migration replaces any migration between the same two versions which is expressed by
+    /// the models' migration targets.
+    pub fn register_migration<From, To>(
+        mut self,
+        migration: impl Fn(From) -> anyhow::Result<To> + 'static,
+    ) -> Self
+    where
+        From: SettingsModel,
+        To: SettingsModel,
+    {
+        let migration = RegisteredMigration::new(migration);
+        self.migrations.add_edge(MigrationEdge {
+            from: migration.from(),
+            to: migration.to(),
+            direction: MigrationDirection::Registered,
+        });
+        self.registered_migrations
+            .insert((migration.from(), migration.to()), migration);
+        self
+    }
+
+    /// Registers a named template helper for a model version, replacing any helper with the same
+    /// name for that version.
+    ///
+    /// Helpers are invoked with the `helper` command, and can be called from templates rendered
+    /// for the version.
+    pub fn register_helper<M>(
+        mut self,
+        name: impl Into<String>,
+        helper: impl Fn(&HelperArgs) -> anyhow::Result<String> + Send + Sync + 'static,
+    ) -> Self
+    where
+        M: SettingsModel,
+    {
+        let helper = TemplateHelper::new(name.into(), helper);
+        self.helpers
+            .entry(M::get_version())
+            .or_default()
+            .insert(helper.name().to_string(), helper);
+        self
+    }
+
+    /// Returns the template helpers registered for a model version, ordered by name.
+    pub fn helpers(&self, version: &str) -> impl Iterator<Item = &TemplateHelper> {
+        self.helpers
+            .get(version)
+            .into_iter()
+            .flat_map(|helpers| helpers.values())
     }
 
+    /// Invokes a template helper registered for a model version, returning its rendered output.
+    pub fn call_helper(&self, version: &str, name: &str, args: &HelperArgs) -> Result<String> {
+        self.model(version)
+            .context(error::ModelVersionNotFoundSnafu { version })?;
+        self.helpers
+            .get(version)
+            .and_then(|helpers| helpers.get(name))
+            .context(error::HelperNotFoundSnafu { version, name })?
+            .call(args)
+            .context(error::HelperFailedSnafu { version, name })
+    }
+
+    /// Registers the handlebars template for a model version, replacing any previously registered
+    /// template. The template is rendered with the `render` command, and may call the helpers
+    /// registered for the version.
+    ///
+    /// Templates which cannot be parsed are reported by `model_diagnostics`.
+    #[cfg(feature = "handlebars")]
+    pub fn register_template<M>(mut self, template: impl Into<String>) -> Self
+    where
+        M: SettingsModel,
+    {
+        self.templates.insert(M::get_version(), template.into());
+        self
+    }
+
+    /// Returns whether a template is registered for a model version.
+    pub fn has_template(&self, version: &str) -> bool {
+        self.templates.contains_key(version)
+    }
+
+    /// Renders a stored value with the template registered for its model version.
+    ///
+    /// The value is first parsed as the model, so templates only see values in the form the model
+    /// gives them. Rendering requires the `handlebars` feature; without it, no version provides a
+    /// template.
+    pub fn render_template(&self, version: &str, value: &serde_json::Value) -> Result<String> {
+        let model = self
+            .model(version)
+            .context(error::ModelVersionNotFoundSnafu { version })?;
+        let template = self
+            .templates
+            .get(version)
+            .context(error::TemplateNotAvailableSnafu { version })?;
+        let value = model.normalize(value.clone())?;
+        crate::template::render(self, version, template, &value)
+    }
+
+    /// Replaces the strategy used to migrate values between model versions.
+    pub fn with_migrator(mut self, migrator: impl Migrator + 'static) -> Self {
+        self.migrator = Box::new(migrator);
+        self
+    }
+
+    /// Parses the extension's command line arguments and runs the requested command, printing its
+    /// output. If the command fails, an `ErrorReport` is printed to stderr and the process exits
+    /// with the error's exit code.
     pub fn run_extension(self) -> Result<()> {
-        let args = cli::Cli::parse_args();
-        match args.protocol {
-            cli::Protocol::Proto1(p) => crate::extension::proto1::run_extension(self, p.command),
+        let result = match cli::Cli::try_parse() {
+            Ok(args) => {
+                let mut stdin = std::io::stdin();
+                self.execute(
+                    args,
+                    &mut Input::new(&mut stdin),
+                    &mut std::io::stdout().lock(),
+                )
+            }
+            // Requests for help or the version are answered by clap.
+            Err(e) if !e.use_stderr() => e.exit(),
+            Err(e) => Err(invalid_args(&e)),
+        };
+        if let Err(e) = result {
+            eprintln!("{}", error_report(&e));
+            std::process::exit(e.kind().exit_code());
         }
         Ok(())
     }
 
-    fn model(&self, version: &str) -> Option<&dyn Model> {
+    /// Runs a command in-process, capturing what the extension would have written to stdout and
+    /// stderr and the code it would have exited with. The first argument is the program name, as
+    /// with `std::env::args`.
+    ///
+    /// The command's stdin is empty, so arguments which are read from stdin, such as `-` and the
+    /// `request` envelope, fail to parse; use `invoke_with_stdin` to provide them.
+    pub fn invoke<I, T>(&self, args: I) -> Output
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        self.invoke_with_stdin(args, std::io::empty())
+    }
+
+    /// Runs a command in-process like `invoke`, giving it `stdin` as its stdin.
+    pub fn invoke_with_stdin<I, T>(&self, args: I, mut stdin: impl Read) -> Output
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut stdout = Vec::new();
+        let result = match cli::Cli::try_parse_from(args) {
+            Ok(args) => {
+                // Each invocation may read its stdin once, as a separate process would.
+                self.execute(args, &mut Input::new(&mut stdin), &mut stdout)
+            }
+            // Help and the version are printed as clap would print them.
+            Err(e) if !e.use_stderr() => {
+                stdout.extend(e.render().to_string().into_bytes());
+                Ok(())
+            }
+            Err(e) => Err(invalid_args(&e)),
+        };
+
+        let (stderr, exit_code) = match result {
+            Ok(()) => (String::new(), 0),
+            Err(e) => (format!("{}\n", error_report(&e)), e.kind().exit_code()),
+        };
+        Output {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr,
+            exit_code,
+        }
+    }
+
+    /// Runs a parsed command, writing its output to `stdout`.
+    fn execute(&self, args: cli::Cli, input: &mut Input, stdout: &mut dyn Write) -> Result<()> {
+        let command_output = match args.command {
+            cli::Command::Protocol(protocol) => match protocol {
+                cli::Protocol::Proto1(p) => proto1::run_extension(self, p.command, input)?,
+                cli::Protocol::Proto2(p) => proto2::run_extension(self, p.command, input)?,
+            },
+            cli::Command::Extension(command) => match command {
+                cli::ExtensionCommand::Negotiate(n) => {
+                    let negotiation = self.negotiate_protocol(&n.accept)?;
+                    serde_json::to_string_pretty(&negotiation).context(error::WriteResultSnafu)?
+                }
+                cli::ExtensionCommand::GenerateConfig(g) => {
+                    self.generate_config(g.name, g.default_version)?.to_toml()?
+                }
+                cli::ExtensionCommand::Describe => serde_json::to_string_pretty(&self.describe())
+                    .context(error::WriteResultSnafu)?,
+                // The server writes its own responses.
+                cli::ExtensionCommand::Serve(s) => {
+                    return match s.socket {
+                        Some(path) => self.serve_unix_socket(path),
+                        None => server::serve_stdio(self, input.stdin()?, stdout),
+                    };
+                }
+            },
+        };
+        writeln!(stdout, "{}", command_output).context(error::WriteOutputSnafu)
+    }
+
+    /// Generates the extension config file describing this extension's models.
+    ///
+    /// The supported versions are the registered models, in the order they were registered. The
+    /// required and validated settings are the union of those declared by each model, and each
+    /// version's templating helpers are those registered for it.
+    pub fn generate_config(
+        &self,
+        name: impl Into<String>,
+        default_version: impl Into<String>,
+    ) -> Result<ExtensionConfig> {
+        let default_version = default_version.into();
+        self.model(&default_version)
+            .context(error::ModelVersionNotFoundSnafu {
+                version: &default_version,
+            })?;
+
+        let models = || {
+            self.versions
+                .iter()
+                .filter_map(|version| self.model(version))
+        };
+        let required_settings: BTreeSet<_> = models()
+            .flat_map(|model| model.required_settings())
+            .map(|setting| setting.name.to_string())
+            .collect();
+        let validated_settings: BTreeSet<_> = models()
+            .flat_map(|model| model.validated_settings())
+            .map(|setting| setting.name.to_string())
+            .collect();
+
+        let config = ExtensionConfig {
+            extension: ExtensionInfo {
+                name: name.into(),
+                supported_versions: self.versions.iter().map(|v| v.to_string()).collect(),
+                default_version,
+            },
+            generation: GenerationConfig {
+                required_settings: required_settings.into_iter().collect(),
+            },
+            validation: ValidationConfig {
+                validated_settings: validated_settings.into_iter().collect(),
+            },
+            templating: self
+                .helpers
+                .iter()
+                .filter(|(version, helpers)| self.model(version).is_some() && !helpers.is_empty())
+                .map(|(version, helpers)| {
+                    let templating = TemplatingConfig {
+                        helpers: helpers.keys().cloned().collect(),
+                    };
+                    (version.to_string(), templating)
+                })
+                .collect(),
+        };
+
+        let problems = ConfigParser::new().validate(&config);
+        ensure!(problems.is_empty(), error::ConfigInvalidSnafu { problems });
+        Ok(config)
+    }
+
+    /// Answers newline-delimited JSON-RPC requests read from stdin until stdin is closed or a
+    /// `shutdown` request is received. See the `serve` command for the supported methods.
+    pub fn serve_stdio(&self) -> Result<()> {
+        server::serve_stdio(self, std::io::stdin(), std::io::stdout().lock())
+    }
+
+    /// Answers newline-delimited JSON-RPC requests from clients of a Unix domain socket, which is
+    /// created at the given path, until a client sends a `shutdown` request. A socket left at the
+    /// path by a previous server is replaced, unless a server is still listening on it.
+    pub fn serve_unix_socket(&self, path: impl AsRef<Path>) -> Result<()> {
+        server::serve_unix_socket(self, path.as_ref())
+    }
+
+    /// Describes the protocols, model versions, migrations and operations supported by this
+    /// extension.
+    pub fn describe(&self) -> ExtensionDescription {
+        describe::describe(self)
+    }
+
+    /// Selects the protocol to use with a caller which accepts the given protocols, in order of
+    /// preference.
+    pub fn negotiate_protocol(&self, accepted: &[String]) -> Result<ProtocolNegotiation> {
+        negotiate::negotiate(accepted)
+    }
+
+    /// Returns the model registered for the given version.
+    pub fn model(&self, version: &str) -> Option<&dyn Model> {
         self.models.get(version).map(|i| i.as_ref())
     }
 
-    // TODO abstract migration out into a pluggable "migrator"
+    /// Returns the graph of migrations between the models registered with this extension.
+    pub fn migrations(&self) -> &MigrationGraph {
+        &self.migrations
+    }
+
     /// Performs a migration of data from a starting model version to a requested one.
     pub(crate) fn perform_migration(
         &self,
@@ -42,114 +423,85 @@ impl SettingsExtension {
         starting_version: &str,
         target_version: &str,
     ) -> Result<serde_json::Value> {
-        let starting_model = self.models.get(starting_version).context(format!(
-            "Could not find model for starting version '{}'",
-            starting_version
-        ))?;
-        self
-            .find_migration_route(starting_version, target_version)
-            .context(format!(
-                "Could not find a defined migration for '{}' to '{}'",
-                starting_version, target_version
-            ))?
-            .try_fold(
-                (starting_value, starting_model),
-                |(curr_value, curr_model), next| match next {
-                    // TODO moving to `snafu` will clean up a lot of these error types.
-                    MigrationDirection::Forward => {
-                        let next_model = self
-                            .models
-                            .get(curr_model.migrates_forward_to().context(
-                                "Failed to find forward migration which was previously found.",
-                            )?)
-                            .context(
-                                "Failed to find forward migration which was previously found.",
-                            )?;
-                        let next_value = curr_model.migrate_forward(curr_value)
-                            .context(format!("Failed to perform sub-migration from '{}' to '{}' as part of migration from '{}' to '{}'",
-                                curr_model.get_version(), next_model.get_version(), starting_version, target_version))?;
-                        Ok((next_value, next_model))
-                    }
-                    MigrationDirection::Backward => {
-                        let next_model = self
-                            .models
-                            .get(curr_model.migrates_backward_to().context(
-                                "Failed to find backward migration which was previously found.",
-                            )?)
-                            .context(
-                                "Failed to find backward migration which was previously found.",
-                            )?;
-                        let next_value = curr_model.migrate_backward(curr_value)
-                            .context(format!("Failed to perform sub-migration from '{}' to '{}' as part of migration from '{}' to '{}'",
-                                curr_model.get_version(), next_model.get_version(), starting_version, target_version))?;
-                        Ok((next_value, next_model))
-                    }
-                },
-            )
-            .map(|(final_value, _)| final_value)
-    }
-
-    /// Returns an iterator of migrations to be performed to transform data from a starting version to a target version.
-    fn find_migration_route(
+        self.model(starting_version)
+            .context(error::ModelVersionNotFoundSnafu {
+                version: starting_version,
+            })?;
+        self.model(target_version)
+            .context(error::ModelVersionNotFoundSnafu {
+                version: target_version,
+            })?;
+        self.migrator
+            .perform_migration(self, starting_value, starting_version, target_version)
+    }
+
+    /// Migrates each of the given values to `target_version` with the extension's migrator,
+    /// reporting the outcome of each separately.
+    pub fn migrate_batch(
         &self,
-        starting_version: &str,
+        entries: Vec<BatchMigrationEntry>,
         target_version: &str,
-    ) -> Option<impl Iterator<Item = MigrationDirection>> {
-        // Searches through the migrations in the given direction. If we find the target version,
-        // we return the number of migrations to perform in the given direction to reach that version.
-        let search_in_direction = |direction: MigrationDirection| {
-            self.migration_iter(starting_version, direction)
-                .enumerate()
-                .find(|(_ndx, model)| model.get_version() == target_version)
-                .map(|(ndx, _)| (ndx, direction))
-        };
-        let search_forward = || search_in_direction(MigrationDirection::Forward);
-        let search_backward = || search_in_direction(MigrationDirection::Backward);
-
-        search_forward()
-            .or_else(search_backward)
-            .map(|(ndx, direction)| std::iter::repeat(direction).take(ndx))
+    ) -> Vec<BatchMigrationResult> {
+        self.migrator.migrate_batch(self, entries, target_version)
     }
 
-    /// Iterate through the extensions chain of model migrations, starting at a given version.
-    fn migration_iter(
+    /// Transforms a value by performing each migration in the given route.
+    pub fn migrate_along(
         &self,
-        starting_version: &str,
-        direction: MigrationDirection,
-    ) -> MigrationIter {
-        MigrationIter {
-            models: &self.models,
-            next: self.models.get(starting_version).map(|i| i.as_ref()),
-            direction,
-        }
-    }
-}
+        route: &MigrationRoute,
+        starting_value: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let (starting_version, target_version) = match (route.steps().first(), route.steps().last())
+        {
+            (Some(first), Some(last)) => (first.from, last.to),
+            _ => return Ok(starting_value),
+        };
 
-/// Helper type for `SettingsExtension` designed to iterate through linear model migration chains.
-struct MigrationIter<'a> {
-    models: &'a HashMap<String, Box<dyn Model>>,
-    next: Option<&'a dyn Model>,
-    direction: MigrationDirection,
+        route
+            .steps()
+            .iter()
+            .try_fold(starting_value, |curr_value, step| {
+                let curr_model = self
+                    .model(step.from)
+                    .context(error::ModelVersionNotFoundSnafu { version: step.from })?;
+                match step.direction {
+                    MigrationDirection::Forward => curr_model.migrate_forward(curr_value),
+                    MigrationDirection::Backward => curr_model.migrate_backward(curr_value),
+                    MigrationDirection::Registered => self
+                        .registered_migrations
+                        .get(&(step.from, step.to))
+                        .context(error::MigrationRouteNotFoundSnafu {
+                            starting_version: step.from,
+                            target_version: step.to,
+                        })
+                        .and_then(|migration| migration.migrate(curr_value)),
+                }
+                .context(error::MigrationFailedSnafu {
+                    from_version: step.from,
+                    to_version: step.to,
+                    starting_version,
+                    target_version,
+                })
+            })
+    }
 }
 
-impl<'a> Iterator for MigrationIter<'a> {
-    type Item = &'a dyn Model;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.next?;
-
-        self.next = match &self.direction {
-            MigrationDirection::Forward => next.migrates_forward_to(),
-            MigrationDirection::Backward => next.migrates_backward_to(),
-        }
-        .and_then(|next_version| self.models.get(next_version).map(|i| i.as_ref()));
-
-        Some(next)
+/// Converts arguments rejected by clap into an error, dropping the usage information which clap
+/// appends to its messages.
+fn invalid_args(e: &clap::Error) -> error::Error {
+    let rendered = e.render().to_string();
+    let message: Vec<&str> = rendered
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::trim)
+        .collect();
+    error::ParseArgsSnafu {
+        message: message.join(" ").trim_start_matches("error: "),
     }
+    .build()
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum MigrationDirection {
-    Forward,
-    Backward,
+/// Formats the `ErrorReport` which is written to stderr when a command fails.
+fn error_report(e: &error::Error) -> String {
+    serde_json::to_string_pretty(&ErrorReport::from(e)).unwrap_or_else(|_| e.to_string())
 }
@@ -0,0 +1,35 @@
+use serde::Serialize;
+use snafu::OptionExt;
+
+use crate::cli::SUPPORTED_PROTOCOLS;
+use crate::error::{self, Result};
+
+/// The outcome of the `negotiate` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProtocolNegotiation {
+    /// Every protocol the extension supports, from oldest to newest.
+    pub supported: Vec<&'static str>,
+    /// The protocol the caller should use.
+    pub selected: &'static str,
+}
+
+/// Selects the first of the caller's accepted protocols which the extension supports, or the
+/// newest supported protocol if the caller did not list any.
+pub(super) fn negotiate(accepted: &[String]) -> Result<ProtocolNegotiation> {
+    let selected = if accepted.is_empty() {
+        SUPPORTED_PROTOCOLS.last().copied()
+    } else {
+        accepted.iter().find_map(|protocol| {
+            SUPPORTED_PROTOCOLS
+                .iter()
+                .copied()
+                .find(|supported| supported == protocol)
+        })
+    };
+
+    Ok(ProtocolNegotiation {
+        supported: SUPPORTED_PROTOCOLS.to_vec(),
+        selected: selected.context(error::NoCommonProtocolSnafu { accepted })?,
+    })
+}
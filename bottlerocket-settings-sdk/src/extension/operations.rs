@@ -0,0 +1,155 @@
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::cli::input::{Input, JsonArg};
+use crate::cli::proto1::{
+    BatchMigrationEntries, GenerateCommand, HelperCommand, MigrateBatchCommand, MigrateCommand,
+    RenderCommand, SchemaCommand, SetCommand, ValidateCommand,
+};
+use crate::error::{self, Result};
+use crate::SettingsExtension;
+
+/// The operations offered by every settings extension protocol. Each protocol parses its own
+/// arguments and formats its own output, but shares these implementations.
+///
+/// JSON arguments are read from `input` by the operations themselves.
+pub(crate) trait Operations {
+    fn set(&self, args: SetCommand, input: &mut Input) -> Result<serde_json::Value>;
+    fn generate(&self, args: GenerateCommand, input: &mut Input) -> Result<serde_json::Value>;
+    fn migrate(&self, args: MigrateCommand, input: &mut Input) -> Result<serde_json::Value>;
+    fn migrate_batch(
+        &self,
+        args: MigrateBatchCommand,
+        input: &mut Input,
+    ) -> Result<serde_json::Value>;
+    fn validate(&self, args: ValidateCommand, input: &mut Input) -> Result<serde_json::Value>;
+    fn schema(&self, args: SchemaCommand) -> Result<serde_json::Value>;
+    fn helper(&self, args: HelperCommand, input: &mut Input) -> Result<serde_json::Value>;
+    fn render(&self, args: RenderCommand, input: &mut Input) -> Result<serde_json::Value>;
+}
+
+/// Reads an optional JSON argument.
+fn read_optional<T: serde::de::DeserializeOwned>(
+    arg: Option<JsonArg<T>>,
+    input: &mut Input,
+) -> Result<Option<T>> {
+    arg.map(|arg| arg.read(input)).transpose()
+}
+
+impl Operations for SettingsExtension {
+    fn set(&self, args: SetCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let model =
+            self.model(&args.setting_version)
+                .context(error::ModelVersionNotFoundSnafu {
+                    version: &args.setting_version,
+                })?;
+        let target = args.value.read(input)?;
+        let current_value = read_optional(args.current_value, input)?;
+        let validated_settings = read_optional(args.validated_settings, input)?;
+        let value = model.set(current_value, target)?;
+
+        // Models may not accept values which they would reject during validation.
+        if model.validates_on_set() {
+            // Without the settings which the model validates against, only the checks which do not
+            // depend on them can be made.
+            let report = match validated_settings {
+                None if !model.validated_settings().is_empty() => {
+                    model.validate_fields(value.clone())?
+                }
+                validated_settings => model.validate(value.clone(), validated_settings)?,
+            };
+            ensure!(
+                report.is_valid(),
+                error::ValidationFailedSnafu {
+                    version: &args.setting_version,
+                    report,
+                }
+            );
+        }
+        Ok(value)
+    }
+
+    fn generate(&self, args: GenerateCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let model =
+            self.model(&args.setting_version)
+                .context(error::ModelVersionNotFoundSnafu {
+                    version: &args.setting_version,
+                })?;
+        let existing_partial = read_optional(args.existing_partial, input)?;
+        let required_settings = read_optional(args.required_settings, input)?;
+        model
+            .generate(existing_partial, required_settings)
+            .and_then(|generated_data| {
+                serde_json::to_value(generated_data).context(error::SerializeOutputSnafu {
+                    version: &args.setting_version,
+                    output: "generated data",
+                })
+            })
+    }
+
+    fn migrate(&self, args: MigrateCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let value = args.value.read(input)?;
+        self.perform_migration(value, &args.from_version, &args.target_version)
+    }
+
+    fn migrate_batch(
+        &self,
+        args: MigrateBatchCommand,
+        input: &mut Input,
+    ) -> Result<serde_json::Value> {
+        let entries = BatchMigrationEntries::read(args.values, input)?;
+        let results = self.migrate_batch(entries.0, &args.target_version);
+        serde_json::to_value(results).context(error::WriteResultSnafu)
+    }
+
+    fn validate(&self, args: ValidateCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let model =
+            self.model(&args.setting_version)
+                .context(error::ModelVersionNotFoundSnafu {
+                    version: &args.setting_version,
+                })?;
+        let value = args.value.read(input)?;
+        let required_settings = read_optional(args.required_settings, input)?;
+        model
+            .validate(value, required_settings)
+            .and_then(|validation| {
+                serde_json::to_value(validation).context(error::SerializeOutputSnafu {
+                    version: &args.setting_version,
+                    output: "validation report",
+                })
+            })
+    }
+
+    /// Returns the schema of the requested version, or an object mapping each version to its
+    /// schema. Versions which do not publish a schema are mapped to `null`.
+    fn schema(&self, args: SchemaCommand) -> Result<serde_json::Value> {
+        match args.setting_version {
+            Some(version) => self
+                .model(&version)
+                .context(error::ModelVersionNotFoundSnafu { version: &version })?
+                .json_schema()
+                .context(error::SchemaNotAvailableSnafu { version }),
+            None => Ok(self
+                .versions
+                .iter()
+                .filter_map(|version| self.model(version))
+                .map(|model| {
+                    let schema = model.json_schema().unwrap_or(serde_json::Value::Null);
+                    (model.get_version().to_string(), schema)
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into()),
+        }
+    }
+
+    fn helper(&self, args: HelperCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let helper_args = read_optional(args.args, input)?.unwrap_or_default();
+        self.call_helper(&args.setting_version, &args.name, &helper_args)
+            .map(serde_json::Value::String)
+    }
+
+    fn render(&self, args: RenderCommand, input: &mut Input) -> Result<serde_json::Value> {
+        let value = args.value.read(input)?;
+        self.render_template(&args.setting_version, &value)
+            .map(serde_json::Value::String)
+    }
+}
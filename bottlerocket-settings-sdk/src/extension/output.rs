@@ -0,0 +1,31 @@
+use crate::error::{ErrorKind, ErrorReport};
+
+/// The captured result of running an extension command with `SettingsExtension::invoke`, as the
+/// extension's process would have produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+impl Output {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Parses the command's stdout as JSON.
+    pub fn json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_str(&self.stdout)
+    }
+
+    /// Parses the error report which a failed command writes to stderr.
+    pub fn error_report(&self) -> Option<ErrorReport> {
+        serde_json::from_str(&self.stderr).ok()
+    }
+
+    /// The kind of error which the command failed with, if it failed.
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        self.error_report().map(|report| report.kind)
+    }
+}
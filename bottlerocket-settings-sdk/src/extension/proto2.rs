@@ -0,0 +1,31 @@
+use serde::Serialize;
+use snafu::ResultExt;
+
+use crate::cli::input::Input;
+use crate::cli::proto1::Proto1Command;
+use crate::error::{self, Result};
+use crate::SettingsExtension;
+
+/// The output of every protocol 2 command, which identifies the protocol and operation that
+/// produced the result.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Proto2Response {
+    protocol: &'static str,
+    operation: &'static str,
+    result: serde_json::Value,
+}
+
+pub(crate) fn run_extension(
+    extension: &SettingsExtension,
+    cmd: Proto1Command,
+    input: &mut Input,
+) -> Result<String> {
+    let (operation, result) = super::proto1::run_command(extension, cmd, input)?;
+    let response = Proto2Response {
+        protocol: "proto2",
+        operation,
+        result,
+    };
+    serde_json::to_string_pretty(&response).context(error::WriteResultSnafu)
+}
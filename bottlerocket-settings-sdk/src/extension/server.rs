@@ -0,0 +1,263 @@
+//! A long-running server which answers JSON-RPC 2.0 requests, avoiding the cost of starting the
+//! extension once per operation.
+//!
+//! Requests and responses are newline-delimited JSON. The `set`, `generate`, `validate`,
+//! `migrate`, `migrate-batch`, `helper` and `render` methods take the same parameters as the
+//! corresponding proto1 commands, e.g.
+//!
+//! ```text
+//! {"jsonrpc": "2.0", "id": 1, "method": "migrate",
+//!  "params": {"value": {...}, "from-version": "v1", "target-version": "v2"}}
+//! ```
+//!
+//! The `shutdown` method stops the server once it has answered. The server also stops when its
+//! input is closed.
+//!
+//! Failed operations are answered with an error code of `-32000` minus the exit code of the
+//! error's kind, and with the `ErrorReport` of the failure as the error's data.
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use snafu::ResultExt;
+
+use super::operations::Operations;
+use crate::cli::input::Input;
+use crate::error::{self, Error, ErrorReport, Result};
+use crate::SettingsExtension;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const OPERATION_FAILED: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    /// `None` if the request is a notification, which is not answered.
+    #[serde(default, deserialize_with = "present")]
+    id: Option<serde_json::Value>,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+/// Distinguishes a `null` id, which must be answered, from an absent one.
+fn present<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<serde_json::Value>, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: serde_json::Value,
+    #[serde(flatten)]
+    outcome: RpcOutcome,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum RpcOutcome {
+    Result(serde_json::Value),
+    Error(RpcError),
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Box<ErrorReport>>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(error: Error) -> Self {
+        Self {
+            code: OPERATION_FAILED - i64::from(error.kind().exit_code()),
+            message: error.to_string(),
+            data: Some(Box::new(ErrorReport::from(&error))),
+        }
+    }
+}
+
+/// Whether the server should keep answering requests after a connection closes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Control {
+    Continue,
+    Shutdown,
+}
+
+/// Answers requests read from stdin on the given writer, until stdin is closed or `shutdown` is
+/// requested.
+pub(super) fn serve_stdio(
+    extension: &SettingsExtension,
+    stdin: impl Read,
+    stdout: impl Write,
+) -> Result<()> {
+    serve_connection(extension, BufReader::new(stdin), stdout).map(|_| ())
+}
+
+/// Answers requests from each client of a Unix domain socket at the given path, one client at a
+/// time, until a client requests `shutdown`. The socket is removed when the server stops.
+///
+/// A client whose connection fails is dropped, and its error is reported on stderr; only a failure
+/// to accept connections stops the server.
+pub(super) fn serve_unix_socket(extension: &SettingsExtension, path: &Path) -> Result<()> {
+    let listener = bind(path).context(error::BindSocketSnafu { path })?;
+    let _socket = RemoveOnDrop(path);
+
+    for stream in listener.incoming() {
+        let stream = stream.context(error::ServerIoSnafu)?;
+        let served = stream
+            .try_clone()
+            .context(error::ServerIoSnafu)
+            .and_then(|reader| serve_connection(extension, BufReader::new(reader), stream));
+        match served {
+            Ok(Control::Shutdown) => break,
+            Ok(Control::Continue) => {}
+            Err(e) => eprintln!("{}", super::error_report(&e)),
+        }
+    }
+    Ok(())
+}
+
+/// Binds a Unix domain socket at the given path. A socket left behind by a server which was killed
+/// before it could remove it is replaced, so long as nothing is listening on it.
+fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    match UnixListener::bind(path) {
+        Err(e) if e.kind() == ErrorKind::AddrInUse && is_stale_socket(path) => {
+            std::fs::remove_file(path)?;
+            UnixListener::bind(path)
+        }
+        result => result,
+    }
+}
+
+fn is_stale_socket(path: &Path) -> bool {
+    let is_socket = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket());
+    is_socket && UnixStream::connect(path).is_err_and(|e| e.kind() == ErrorKind::ConnectionRefused)
+}
+
+/// Removes the server's socket once it stops listening.
+struct RemoveOnDrop<'a>(&'a Path);
+
+impl Drop for RemoveOnDrop<'_> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.0);
+    }
+}
+
+fn serve_connection<R: BufRead, W: Write>(
+    extension: &SettingsExtension,
+    reader: R,
+    mut writer: W,
+) -> Result<Control> {
+    for line in reader.split(b'\n') {
+        let line = line.context(error::ServerIoSnafu)?;
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let (response, control) = handle_request(extension, &line);
+        if let Some(response) = response {
+            serde_json::to_writer(&mut writer, &response).context(error::WriteResultSnafu)?;
+            writer.write_all(b"\n").context(error::ServerIoSnafu)?;
+            writer.flush().context(error::ServerIoSnafu)?;
+        }
+        if control == Control::Shutdown {
+            return Ok(Control::Shutdown);
+        }
+    }
+    Ok(Control::Continue)
+}
+
+fn handle_request(extension: &SettingsExtension, line: &[u8]) -> (Option<RpcResponse>, Control) {
+    let respond = |id: serde_json::Value, outcome| RpcResponse {
+        jsonrpc: "2.0",
+        id,
+        outcome,
+    };
+
+    let request = match parse_request(line) {
+        Ok(request) => request,
+        Err(error) => {
+            let response = respond(serde_json::Value::Null, RpcOutcome::Error(error));
+            return (Some(response), Control::Continue);
+        }
+    };
+
+    let outcome = match dispatch(extension, &request.method, request.params) {
+        Ok(result) => RpcOutcome::Result(result),
+        Err(error) => RpcOutcome::Error(error),
+    };
+    let control = if request.method == "shutdown" {
+        Control::Shutdown
+    } else {
+        Control::Continue
+    };
+
+    (request.id.map(|id| respond(id, outcome)), control)
+}
+
+fn parse_request(line: &[u8]) -> std::result::Result<RpcRequest, RpcError> {
+    // Lines which are not UTF-8 are rejected by the JSON parser, like any other malformed request.
+    let request: serde_json::Value =
+        serde_json::from_slice(line).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))?;
+    let request: RpcRequest = serde_json::from_value(request)
+        .map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))?;
+    if request.jsonrpc != "2.0" {
+        return Err(RpcError::new(
+            INVALID_REQUEST,
+            "only JSON-RPC 2.0 is supported",
+        ));
+    }
+    Ok(request)
+}
+
+fn dispatch(
+    extension: &SettingsExtension,
+    method: &str,
+    params: serde_json::Value,
+) -> std::result::Result<serde_json::Value, RpcError> {
+    // Parameters are always parsed from the request, so operations have no stdin to read.
+    let mut stdin = std::io::empty();
+    let input = &mut Input::new(&mut stdin);
+    match method {
+        "set" => Ok(extension.set(params_for(params)?, input)?),
+        "generate" => Ok(extension.generate(params_for(params)?, input)?),
+        "validate" => Ok(extension.validate(params_for(params)?, input)?),
+        "migrate" => Ok(extension.migrate(params_for(params)?, input)?),
+        "helper" => Ok(extension.helper(params_for(params)?, input)?),
+        "render" => Ok(extension.render(params_for(params)?, input)?),
+        "migrate-batch" => Ok(Operations::migrate_batch(
+            extension,
+            params_for(params)?,
+            input,
+        )?),
+        "shutdown" => Ok(serde_json::Value::Null),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method '{}'", method),
+        )),
+    }
+}
+
+fn params_for<T: DeserializeOwned>(params: serde_json::Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
@@ -1,7 +1,33 @@
 pub mod cli;
+pub mod config;
 pub mod error;
 pub(crate) mod extension;
+pub mod migrate;
 mod model;
+#[cfg(feature = "schemars")]
+pub mod schema;
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod validation;
 
-pub use crate::extension::SettingsExtension;
-pub use model::{BottlerocketSetting, GenerateResult, NoMigration, SettingsModel};
+pub use crate::error::{Error, ErrorKind, ErrorReport};
+pub use crate::extension::{
+    DiagnosticSeverity, ExtensionDescription, ModelDiagnostic, Output, ProtocolNegotiation,
+    SettingsExtension, VersionDescription,
+};
+pub use crate::validation::{ValidationIssue, ValidationReport, ValidationSeverity};
+pub use bottlerocket_settings_derive::{SettingsModel, SettingsPartial};
+pub use model::{
+    BottlerocketSetting, GenerateResult, Model, NoDependentSettings, NoMigration, Partial,
+    SettingDependency, SettingsModel,
+};
+
+/// Dependencies referenced by code generated from the SDK's derive macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use anyhow;
+    pub use regex;
+    pub use serde;
+    pub use serde_json;
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use snafu::OptionExt;
+
+use super::{MigrationRoute, Migrator};
+use crate::error::{self, ErrorReport, Result};
+use crate::SettingsExtension;
+
+/// A value to be migrated as part of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BatchMigrationEntry {
+    pub value: serde_json::Value,
+    #[serde(alias = "from_version")]
+    pub from_version: String,
+}
+
+/// The outcome of migrating a single entry of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BatchMigrationResult {
+    Migrated(serde_json::Value),
+    Failed(ErrorReport),
+}
+
+/// Migrates each entry to `target_version` with the given migrator, finding the route from each
+/// starting version once.
+pub(super) fn migrate_batch<M: Migrator + ?Sized>(
+    migrator: &M,
+    extension: &SettingsExtension,
+    entries: Vec<BatchMigrationEntry>,
+    target_version: &str,
+) -> Vec<BatchMigrationResult> {
+    let mut routes: HashMap<String, std::result::Result<Rc<MigrationRoute>, ErrorReport>> =
+        HashMap::new();
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let route = routes
+                .entry(entry.from_version.clone())
+                .or_insert_with(|| {
+                    route_to(migrator, extension, &entry.from_version, target_version)
+                        .map(Rc::new)
+                        .map_err(|e| ErrorReport::from(&e))
+                })
+                .clone();
+
+            match route {
+                Ok(route) => match migrator.migrate_along(extension, &route, entry.value) {
+                    Ok(migrated) => BatchMigrationResult::Migrated(migrated),
+                    Err(e) => BatchMigrationResult::Failed(ErrorReport::from(&e)),
+                },
+                Err(report) => BatchMigrationResult::Failed(report),
+            }
+        })
+        .collect()
+}
+
+fn route_to<M: Migrator + ?Sized>(
+    migrator: &M,
+    extension: &SettingsExtension,
+    starting_version: &str,
+    target_version: &str,
+) -> Result<MigrationRoute> {
+    for version in [starting_version, target_version] {
+        extension
+            .model(version)
+            .context(error::ModelVersionNotFoundSnafu { version })?;
+    }
+    migrator.find_route(extension, starting_version, target_version)
+}
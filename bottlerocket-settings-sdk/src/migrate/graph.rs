@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use serde::Serialize;
+
+use super::Migrator;
+use crate::error::{self, Result};
+use crate::model::Model;
+use crate::SettingsExtension;
+
+/// The direction of a migration between two neighbouring model versions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MigrationDirection {
+    /// The migration is performed by the source model's `migrate_forward`.
+    Forward,
+    /// The migration is performed by the source model's `migrate_backward`.
+    Backward,
+    /// The migration is performed by a function given to `SettingsExtension::register_migration`.
+    Registered,
+}
+
+impl std::fmt::Display for MigrationDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationDirection::Forward => write!(f, "forward"),
+            MigrationDirection::Backward => write!(f, "backward"),
+            MigrationDirection::Registered => write!(f, "registered"),
+        }
+    }
+}
+
+/// A single migration from one model version to another.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MigrationEdge {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub direction: MigrationDirection,
+}
+
+/// The sequence of migrations which transforms a value from one version into another.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct MigrationRoute {
+    steps: Vec<MigrationEdge>,
+}
+
+impl MigrationRoute {
+    pub fn new(steps: Vec<MigrationEdge>) -> Self {
+        Self { steps }
+    }
+
+    /// The migrations to perform, in order.
+    pub fn steps(&self) -> &[MigrationEdge] {
+        &self.steps
+    }
+}
+
+/// Directed graph of the migrations available between the models registered with an extension.
+///
+/// Each model contributes an edge to its forward and backward migration targets, so long as the
+/// target is also registered. Migrations registered directly with the extension add further edges.
+/// Routes are found by shortest path, which allows parallel version lines (e.g. an LTS branch
+/// forking from a mainline version) to migrate through their common ancestors, and prefers a
+/// registered migration which skips versions over the chain of migrations it replaces.
+///
+/// Since each model names at most one forward target, the models alone cannot fork: two branches
+/// may migrate backward into a common ancestor, but the ancestor migrates forward into only one of
+/// them. Migrating from the ancestor into any other branch requires a migration registered with
+/// `SettingsExtension::register_migration`.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationGraph {
+    edges: BTreeMap<&'static str, Vec<MigrationEdge>>,
+}
+
+impl MigrationGraph {
+    pub(crate) fn from_models(models: &HashMap<String, Box<dyn Model>>) -> Self {
+        let mut edges: BTreeMap<&'static str, Vec<MigrationEdge>> = BTreeMap::new();
+        for model in models.values() {
+            let from = model.get_version();
+            let neighbours = [
+                (model.migrates_forward_to(), MigrationDirection::Forward),
+                (model.migrates_backward_to(), MigrationDirection::Backward),
+            ];
+            let node = edges.entry(from).or_default();
+            for (to, direction) in neighbours {
+                if let Some(to) = to.filter(|to| models.contains_key(*to)) {
+                    node.push(MigrationEdge {
+                        from,
+                        to,
+                        direction,
+                    });
+                }
+            }
+        }
+        Self { edges }
+    }
+
+    /// Adds a migration to the graph, replacing any existing migration between the same versions.
+    pub(crate) fn add_edge(&mut self, edge: MigrationEdge) {
+        let node = self.edges.entry(edge.from).or_default();
+        node.retain(|existing| existing.to != edge.to);
+        node.push(edge);
+    }
+
+    /// Iterates over every migration in the graph.
+    pub fn edges(&self) -> impl Iterator<Item = &MigrationEdge> {
+        self.edges.values().flatten()
+    }
+
+    /// Returns the migrations which can be performed starting from the given version.
+    pub fn edges_from(&self, version: &str) -> &[MigrationEdge] {
+        self.edges
+            .get(version)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Finds the shortest route of migrations from `starting_version` to `target_version`.
+    ///
+    /// Fails if no route exists, or if more than one route of the shortest length exists, since the
+    /// migrations along each route could produce different values.
+    pub fn find_route(
+        &self,
+        starting_version: &str,
+        target_version: &str,
+    ) -> Result<MigrationRoute> {
+        // Breadth-first search, recording the edge used to reach each version and the number of
+        // distinct shortest paths which reach it.
+        let mut visited: HashMap<&str, (usize, Option<MigrationEdge>, usize)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        visited.insert(starting_version, (0, None, 1));
+        queue.push_back(starting_version);
+
+        while let Some(version) = queue.pop_front() {
+            let (depth, _, paths) = visited[version];
+            for edge in self.edges_from(version) {
+                match visited.get_mut(edge.to) {
+                    None => {
+                        visited.insert(edge.to, (depth + 1, Some(*edge), paths));
+                        queue.push_back(edge.to);
+                    }
+                    Some((to_depth, _, to_paths)) if *to_depth == depth + 1 => {
+                        *to_paths = to_paths.saturating_add(paths);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let (_, _, paths) = visited.get(target_version).copied().ok_or_else(|| {
+            error::MigrationRouteNotFoundSnafu {
+                starting_version,
+                target_version,
+            }
+            .build()
+        })?;
+        snafu::ensure!(
+            paths == 1,
+            error::AmbiguousMigrationRouteSnafu {
+                starting_version,
+                target_version,
+                routes: paths,
+            }
+        );
+
+        let mut steps = Vec::new();
+        let mut current = target_version;
+        while let Some((_, Some(edge), _)) = visited.get(current) {
+            steps.push(*edge);
+            current = edge.from;
+        }
+        steps.reverse();
+
+        Ok(MigrationRoute { steps })
+    }
+}
+
+/// Migrates along the shortest route through the extension's [`MigrationGraph`].
+///
+/// This is the default migrator used by `SettingsExtension`.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct GraphMigrator;
+
+impl Migrator for GraphMigrator {
+    fn find_route(
+        &self,
+        extension: &SettingsExtension,
+        starting_version: &str,
+        target_version: &str,
+    ) -> Result<MigrationRoute> {
+        extension
+            .migrations()
+            .find_route(starting_version, target_version)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph(edges: &[(&'static str, &'static str, MigrationDirection)]) -> MigrationGraph {
+        let mut graph = MigrationGraph::default();
+        for &(from, to, direction) in edges {
+            graph.add_edge(MigrationEdge {
+                from,
+                to,
+                direction,
+            });
+        }
+        graph
+    }
+
+    fn route(graph: &MigrationGraph, from: &str, to: &str) -> Vec<(&'static str, &'static str)> {
+        graph
+            .find_route(from, to)
+            .unwrap()
+            .steps()
+            .iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect()
+    }
+
+    #[test]
+    fn linear() {
+        let graph = graph(&[
+            ("v1", "v2", MigrationDirection::Forward),
+            ("v2", "v3", MigrationDirection::Forward),
+            ("v2", "v1", MigrationDirection::Backward),
+            ("v3", "v2", MigrationDirection::Backward),
+        ]);
+        assert_eq!(route(&graph, "v1", "v3"), [("v1", "v2"), ("v2", "v3")]);
+        assert_eq!(route(&graph, "v3", "v1"), [("v3", "v2"), ("v2", "v1")]);
+        assert!(route(&graph, "v2", "v2").is_empty());
+    }
+
+    #[test]
+    fn branched() {
+        // An LTS branch forks from v2, which migrates forward to mainline v3.
+        let graph = graph(&[
+            ("v1", "v2", MigrationDirection::Forward),
+            ("v2", "v3", MigrationDirection::Forward),
+            ("v3", "v2", MigrationDirection::Backward),
+            ("v2", "v2-lts", MigrationDirection::Registered),
+            ("v2-lts", "v2", MigrationDirection::Backward),
+        ]);
+        assert_eq!(
+            route(&graph, "v2-lts", "v3"),
+            [("v2-lts", "v2"), ("v2", "v3")]
+        );
+        assert_eq!(
+            route(&graph, "v1", "v2-lts"),
+            [("v1", "v2"), ("v2", "v2-lts")]
+        );
+    }
+
+    #[test]
+    fn ambiguous_diamond() {
+        let graph = graph(&[
+            ("v1", "v2a", MigrationDirection::Registered),
+            ("v1", "v2b", MigrationDirection::Registered),
+            ("v2a", "v3", MigrationDirection::Forward),
+            ("v2b", "v3", MigrationDirection::Forward),
+        ]);
+        let error = graph.find_route("v1", "v3").unwrap_err();
+        assert!(matches!(
+            error,
+            error::Error::AmbiguousMigrationRoute { routes: 2, .. }
+        ));
+        assert_eq!(route(&graph, "v1", "v2a"), [("v1", "v2a")]);
+    }
+
+    #[test]
+    fn no_route() {
+        let graph = graph(&[
+            ("v1", "v2", MigrationDirection::Forward),
+            ("v3", "v2", MigrationDirection::Backward),
+        ]);
+        for (from, to) in [("v2", "v1"), ("v1", "v3"), ("v1", "v4")] {
+            let error = graph.find_route(from, to).unwrap_err();
+            assert!(matches!(error, error::Error::MigrationRouteNotFound { .. }));
+        }
+    }
+
+    #[test]
+    fn registered_skip_is_preferred() {
+        let graph = graph(&[
+            ("v1", "v2", MigrationDirection::Forward),
+            ("v2", "v3", MigrationDirection::Forward),
+            ("v1", "v3", MigrationDirection::Registered),
+        ]);
+        assert_eq!(
+            graph.find_route("v1", "v3").unwrap().steps(),
+            [MigrationEdge {
+                from: "v1",
+                to: "v3",
+                direction: MigrationDirection::Registered,
+            }]
+        );
+        assert_eq!(route(&graph, "v1", "v2"), [("v1", "v2")]);
+    }
+}
@@ -0,0 +1,65 @@
+use snafu::OptionExt;
+
+use super::{MigrationDirection, MigrationEdge, MigrationRoute, Migrator};
+use crate::error::{self, Result};
+use crate::SettingsExtension;
+
+/// Migrates by walking the chain of `migrates_forward_to` or `migrates_backward_to` neighbours from
+/// the starting version until the target version is found.
+///
+/// Unlike [`GraphMigrator`](super::GraphMigrator), this never changes direction part-way through a
+/// migration, so versions on separate branches cannot be migrated between.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LinearMigrator;
+
+impl Migrator for LinearMigrator {
+    fn find_route(
+        &self,
+        extension: &SettingsExtension,
+        starting_version: &str,
+        target_version: &str,
+    ) -> Result<MigrationRoute> {
+        [MigrationDirection::Forward, MigrationDirection::Backward]
+            .into_iter()
+            .find_map(|direction| {
+                chain_route(extension, starting_version, target_version, direction)
+            })
+            .context(error::MigrationRouteNotFoundSnafu {
+                starting_version,
+                target_version,
+            })
+    }
+}
+
+/// Walks the chain of models in one direction, returning the route to the target if it is found.
+fn chain_route(
+    extension: &SettingsExtension,
+    starting_version: &str,
+    target_version: &str,
+    direction: MigrationDirection,
+) -> Option<MigrationRoute> {
+    let mut steps = Vec::new();
+    let mut current = extension.model(starting_version)?;
+
+    while current.get_version() != target_version {
+        let next = match direction {
+            MigrationDirection::Forward => current.migrates_forward_to(),
+            MigrationDirection::Backward => current.migrates_backward_to(),
+            MigrationDirection::Registered => None,
+        }
+        .and_then(|version| extension.model(version))?;
+
+        // Guard against cycles in a misconfigured chain.
+        if steps.len() > extension.models.len() {
+            return None;
+        }
+        steps.push(MigrationEdge {
+            from: current.get_version(),
+            to: next.get_version(),
+            direction,
+        });
+        current = next;
+    }
+
+    Some(MigrationRoute::new(steps))
+}
@@ -0,0 +1,78 @@
+//! Migration of settings values between the model versions registered with an extension.
+//!
+//! `SettingsExtension` delegates migrations to a [`Migrator`]. By default, [`GraphMigrator`] is
+//! used, but extensions can supply their own strategy using `SettingsExtension::with_migrator`.
+//!
+//! [`LinearMigrator`] walks the chain of each model's migration targets, as the SDK originally did.
+//! It is not the default because it cannot follow migrations added with
+//! `SettingsExtension::register_migration`, or migrate between branches of a version line.
+mod batch;
+mod graph;
+mod linear;
+mod registered;
+
+use std::fmt::Debug;
+
+pub use batch::{BatchMigrationEntry, BatchMigrationResult};
+pub use graph::{GraphMigrator, MigrationDirection, MigrationEdge, MigrationGraph, MigrationRoute};
+pub use linear::LinearMigrator;
+pub(crate) use registered::RegisteredMigration;
+
+use crate::error::Result;
+use crate::SettingsExtension;
+
+/// A strategy for migrating settings values between model versions.
+pub trait Migrator: Debug {
+    /// Finds the route of migrations which transforms a value from `starting_version` to
+    /// `target_version`.
+    ///
+    /// Both versions are guaranteed to be registered with the extension.
+    fn find_route(
+        &self,
+        extension: &SettingsExtension,
+        starting_version: &str,
+        target_version: &str,
+    ) -> Result<MigrationRoute>;
+
+    /// Migrates a value from `starting_version` to `target_version`.
+    ///
+    /// By default, this migrates the value along the route returned by `find_route`. Migrators
+    /// which override this, rather than `migrate_along`, should also override `migrate_batch`.
+    fn perform_migration(
+        &self,
+        extension: &SettingsExtension,
+        starting_value: serde_json::Value,
+        starting_version: &str,
+        target_version: &str,
+    ) -> Result<serde_json::Value> {
+        let route = self.find_route(extension, starting_version, target_version)?;
+        self.migrate_along(extension, &route, starting_value)
+    }
+
+    /// Transforms a value by performing each migration in a route found by `find_route`.
+    ///
+    /// By default, this uses `SettingsExtension::migrate_along`.
+    fn migrate_along(
+        &self,
+        extension: &SettingsExtension,
+        route: &MigrationRoute,
+        starting_value: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        extension.migrate_along(route, starting_value)
+    }
+
+    /// Migrates each of the given values to `target_version`, reporting the outcome of each
+    /// separately.
+    ///
+    /// Unlike the other methods, the starting versions of the values are not guaranteed to be
+    /// registered with the extension. By default, this finds the route from each starting version
+    /// once, then migrates each value along its route with `migrate_along`.
+    fn migrate_batch(
+        &self,
+        extension: &SettingsExtension,
+        entries: Vec<BatchMigrationEntry>,
+        target_version: &str,
+    ) -> Vec<BatchMigrationResult> {
+        batch::migrate_batch(self, extension, entries, target_version)
+    }
+}
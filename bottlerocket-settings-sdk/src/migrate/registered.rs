@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+
+use snafu::ResultExt;
+
+use crate::error::{self, Result};
+use crate::SettingsModel;
+
+type ErasedMigration = dyn Fn(serde_json::Value) -> Result<serde_json::Value>;
+
+/// A migration function registered directly with a `SettingsExtension`, rather than expressed
+/// through a model's migration target types.
+pub(crate) struct RegisteredMigration {
+    from: &'static str,
+    to: &'static str,
+    migration: Box<ErasedMigration>,
+}
+
+impl RegisteredMigration {
+    pub(crate) fn new<From, To, F>(migration: F) -> Self
+    where
+        From: SettingsModel,
+        To: SettingsModel,
+        F: Fn(From) -> anyhow::Result<To> + 'static,
+    {
+        let migration = move |value: serde_json::Value| {
+            let value: From =
+                serde_json::from_value(value).context(error::DeserializeInputSnafu {
+                    version: From::get_version(),
+                    input: "migration input",
+                })?;
+            let migrated = migration(value).context(error::ModelCallbackFailedSnafu {
+                version: From::get_version(),
+                operation: "registered-migration",
+            })?;
+            serde_json::to_value(migrated).context(error::SerializeOutputSnafu {
+                version: To::get_version(),
+                output: "migration result",
+            })
+        };
+
+        Self {
+            from: From::get_version(),
+            to: To::get_version(),
+            migration: Box::new(migration),
+        }
+    }
+
+    pub(crate) fn from(&self) -> &'static str {
+        self.from
+    }
+
+    pub(crate) fn to(&self) -> &'static str {
+        self.to
+    }
+
+    pub(crate) fn migrate(&self, value: serde_json::Value) -> Result<serde_json::Value> {
+        (self.migration)(value)
+    }
+}
+
+impl Debug for RegisteredMigration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisteredMigration")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
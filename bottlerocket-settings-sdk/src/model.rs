@@ -1,20 +1,27 @@
 //! Two separate traits are exposed to make a model which functions with the Bottlerocket SDK.
 //!
-//! Consumers will `#[derive(Model)]` and `impl SettingsModel` in order to interface with the SDK.
+//! Consumers either `#[derive(SettingsModel)]` or `impl SettingsModel` by hand in order to
+//! interface with the SDK.
 //!
 //! `SettingsModel` is the developer interface for implementing a model. Because th e
 //!
 use std::fmt::Debug;
 use std::{any::TypeId, marker::PhantomData};
 
-use anyhow::{Context, Result};
-
+use anyhow::Result;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::error;
+use crate::validation::ValidationReport;
 
 pub trait SettingsModel: Sized + Serialize + DeserializeOwned + Debug {
     type PartialType: Serialize + DeserializeOwned;
     type ForwardMigrationTarget: 'static + SettingsModel;
     type BackwardMigrationTarget: 'static + SettingsModel;
+    /// The settings this model receives from the settings it depends on during `generate` and
+    /// `validate`. Use `NoDependentSettings` if the model has no dependencies.
+    type DependentSettings: DeserializeOwned;
 
     fn get_version() -> &'static str;
 
@@ -36,16 +43,80 @@ pub trait SettingsModel: Sized + Serialize + DeserializeOwned + Debug {
     }
     fn migrate_backward(self) -> Result<Self::BackwardMigrationTarget>;
 
+    /// Settings which must be provided to `generate` this model.
+    const REQUIRED_SETTINGS: &'static [SettingDependency] = &[];
+
+    /// Settings which must be provided to `validate` this model.
+    const VALIDATED_SETTINGS: &'static [SettingDependency] = &[];
+
+    /// Whether values produced by `set` are checked with `validate` before they are accepted.
+    ///
+    /// Models should only opt out of this if their values cannot be validated when they are set.
+    const VALIDATE_ON_SET: bool = true;
+
     fn set(current_value: Option<Self>, target: Self) -> Result<Self>;
 
+    /// The JSON Schema describing values of this model, if it publishes one.
+    ///
+    /// With the `schemars` feature, this can be implemented with `schema::json_schema_for`.
+    fn json_schema() -> Option<serde_json::Value> {
+        None
+    }
+
     fn generate(
         existing_partial: Option<Self::PartialType>,
-        dependent_settings: Option<serde_json::Value>,
+        dependent_settings: Option<Self::DependentSettings>,
     ) -> Result<GenerateResult<Self::PartialType, Self>>;
 
-    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<bool>;
+    /// Checks a value, reporting each problem found with it.
+    fn validate(
+        _value: Self,
+        _validated_settings: Option<Self::DependentSettings>,
+    ) -> Result<ValidationReport>;
+
+    /// Makes the checks of `validate` which do not depend on other settings.
+    ///
+    /// `set` uses this in place of `validate` when the model's `VALIDATED_SETTINGS` are not
+    /// provided. Derived models report their field checks here.
+    fn validate_fields(_value: &Self) -> ValidationReport {
+        ValidationReport::new()
+    }
+}
+
+/// Another setting, at a specific version, which a settings model depends on.
+///
+/// Dependent settings are passed to a model as a JSON object, keyed by setting name, which is
+/// deserialized into the model's `DependentSettings` type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct SettingDependency {
+    pub name: &'static str,
+    pub version: &'static str,
 }
 
+impl SettingDependency {
+    pub const fn new(name: &'static str, version: &'static str) -> Self {
+        Self { name, version }
+    }
+}
+
+/// Conversions between a settings model's `PartialType` and the complete model.
+///
+/// This is implemented for partial types generated by `#[derive(SettingsPartial)]`.
+pub trait Partial<Complete>: Sized {
+    /// Returns `complete`, with each field replaced by the corresponding field of this partial if
+    /// that field is populated.
+    fn merge_into(self, complete: Complete) -> Complete;
+
+    /// Promotes this partial to a complete value if every field is populated, otherwise returns
+    /// the partial unchanged.
+    fn try_complete(self) -> std::result::Result<Complete, Self>;
+}
+
+/// `DependentSettings` for models which do not depend on other settings. Any provided settings are
+/// ignored.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct NoDependentSettings {}
+
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
 pub struct NoMigration;
 impl NoMigration {
@@ -61,6 +132,8 @@ impl SettingsModel for NoMigration {
 
     type BackwardMigrationTarget = NoMigration;
 
+    type DependentSettings = NoDependentSettings;
+
     fn get_version() -> &'static str {
         unimplemented!(
             "`NoMigration` used as a marker type. Its settings model should never be used."
@@ -75,14 +148,17 @@ impl SettingsModel for NoMigration {
 
     fn generate(
         _existing_partial: Option<Self::PartialType>,
-        _dependent_settings: Option<serde_json::Value>,
+        _dependent_settings: Option<Self::DependentSettings>,
     ) -> Result<GenerateResult<Self::PartialType, Self>> {
         unimplemented!(
             "`NoMigration` used as a marker type. Its settings model should never be used."
         )
     }
 
-    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<bool> {
+    fn validate(
+        _value: Self,
+        _validated_settings: Option<Self::DependentSettings>,
+    ) -> Result<ValidationReport> {
         unimplemented!(
             "`NoMigration` used as a marker type. Its settings model should never be used."
         )
@@ -109,27 +185,41 @@ pub trait Model: Debug {
 
     fn migrates_backward_to(&self) -> Option<&'static str>;
 
+    fn required_settings(&self) -> &'static [SettingDependency];
+
+    fn validated_settings(&self) -> &'static [SettingDependency];
+
+    fn validates_on_set(&self) -> bool;
+
+    fn json_schema(&self) -> Option<serde_json::Value>;
+
     fn set(
         &self,
         current: Option<serde_json::Value>,
         target: serde_json::Value,
-    ) -> Result<serde_json::Value>;
+    ) -> error::Result<serde_json::Value>;
 
-    fn migrate_forward(&self, current: serde_json::Value) -> Result<serde_json::Value>;
+    fn migrate_forward(&self, current: serde_json::Value) -> error::Result<serde_json::Value>;
 
-    fn migrate_backward(&self, current: serde_json::Value) -> Result<serde_json::Value>;
+    fn migrate_backward(&self, current: serde_json::Value) -> error::Result<serde_json::Value>;
 
     fn generate(
         &self,
         existing_partial: Option<serde_json::Value>,
         dependent_settings: Option<serde_json::Value>,
-    ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>>;
+    ) -> error::Result<GenerateResult<serde_json::Value, serde_json::Value>>;
 
     fn validate(
         &self,
         value: serde_json::Value,
         validated_settings: Option<serde_json::Value>,
-    ) -> Result<bool>;
+    ) -> error::Result<ValidationReport>;
+
+    fn validate_fields(&self, value: serde_json::Value) -> error::Result<ValidationReport>;
+
+    /// Deserializes a value as the model, then serializes it again, so that it takes the form the
+    /// model would give it.
+    fn normalize(&self, value: serde_json::Value) -> error::Result<serde_json::Value>;
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -138,19 +228,29 @@ pub enum GenerateResult<Partial: Serialize, Complete: Serialize> {
     Complete(Option<Complete>),
 }
 
+impl<P: Serialize + Partial<C>, C: Serialize> GenerateResult<P, C> {
+    /// Returns `Complete` if the given partial has every field populated, otherwise returns
+    /// `NeedsData` with the partial.
+    pub fn from_partial(partial: Option<P>) -> Self {
+        match partial.map(Partial::try_complete) {
+            Some(Ok(complete)) => GenerateResult::Complete(Some(complete)),
+            Some(Err(partial)) => GenerateResult::NeedsData(Some(partial)),
+            None => GenerateResult::NeedsData(None),
+        }
+    }
+}
+
 impl<P: Serialize, C: Serialize> GenerateResult<P, C> {
-    pub fn erase_type(self) -> Result<GenerateResult<serde_json::Value, serde_json::Value>> {
+    pub fn erase_type(
+        self,
+    ) -> serde_json::Result<GenerateResult<serde_json::Value, serde_json::Value>> {
         Ok(match self {
-            GenerateResult::NeedsData(optional_interior) => GenerateResult::NeedsData(
-                optional_interior
-                    .map(|i| serde_json::to_value(i))
-                    .transpose()?,
-            ),
-            GenerateResult::Complete(optional_interior) => GenerateResult::Complete(
-                optional_interior
-                    .map(|i| serde_json::to_value(i))
-                    .transpose()?,
-            ),
+            GenerateResult::NeedsData(optional_interior) => {
+                GenerateResult::NeedsData(optional_interior.map(serde_json::to_value).transpose()?)
+            }
+            GenerateResult::Complete(optional_interior) => {
+                GenerateResult::Complete(optional_interior.map(serde_json::to_value).transpose()?)
+            }
         })
     }
 }
@@ -180,30 +280,78 @@ impl<T: SettingsModel> Model for BottlerocketSetting<T> {
         T::migrates_backward_to()
     }
 
+    fn required_settings(&self) -> &'static [SettingDependency] {
+        T::REQUIRED_SETTINGS
+    }
+
+    fn validated_settings(&self) -> &'static [SettingDependency] {
+        T::VALIDATED_SETTINGS
+    }
+
+    fn validates_on_set(&self) -> bool {
+        T::VALIDATE_ON_SET
+    }
+
+    fn json_schema(&self) -> Option<serde_json::Value> {
+        T::json_schema()
+    }
+
     fn set(
         &self,
         current: Option<serde_json::Value>,
         target: serde_json::Value,
-    ) -> Result<serde_json::Value> {
-        let current = current.map(|v| serde_json::from_value(v)).transpose()?;
-        let target = serde_json::from_value(target)?;
-        T::set(current, target).and_then(|retval| {
-            serde_json::to_value(retval).context("Failed to serialize result to JSON")
+    ) -> error::Result<serde_json::Value> {
+        let current = current.map(serde_json::from_value).transpose().context(
+            error::DeserializeInputSnafu {
+                version: T::get_version(),
+                input: "current value",
+            },
+        )?;
+        let target = serde_json::from_value(target).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "target value",
+        })?;
+        let retval = T::set(current, target).context(error::ModelCallbackFailedSnafu {
+            version: T::get_version(),
+            operation: "set",
+        })?;
+        serde_json::to_value(retval).context(error::SerializeOutputSnafu {
+            version: T::get_version(),
+            output: "set result",
         })
     }
 
-    fn migrate_forward(&self, current: serde_json::Value) -> Result<serde_json::Value> {
-        let current: T =
-            serde_json::from_value(current).context("Failed to parse incoming JSON value.")?;
-        current.migrate_forward().and_then(|retval| {
-            serde_json::to_value(retval).context("Failed to serialize result to JSON")
+    fn migrate_forward(&self, current: serde_json::Value) -> error::Result<serde_json::Value> {
+        let current: T = serde_json::from_value(current).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "migration input",
+        })?;
+        let retval = current
+            .migrate_forward()
+            .context(error::ModelCallbackFailedSnafu {
+                version: T::get_version(),
+                operation: "migrate-forward",
+            })?;
+        serde_json::to_value(retval).context(error::SerializeOutputSnafu {
+            version: T::get_version(),
+            output: "migration result",
         })
     }
 
-    fn migrate_backward(&self, current: serde_json::Value) -> Result<serde_json::Value> {
-        let current: T = serde_json::from_value(current)?;
-        current.migrate_backward().and_then(|retval| {
-            serde_json::to_value(retval).context("Failed to serialize result to JSON")
+    fn migrate_backward(&self, current: serde_json::Value) -> error::Result<serde_json::Value> {
+        let current: T = serde_json::from_value(current).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "migration input",
+        })?;
+        let retval = current
+            .migrate_backward()
+            .context(error::ModelCallbackFailedSnafu {
+                version: T::get_version(),
+                operation: "migrate-backward",
+            })?;
+        serde_json::to_value(retval).context(error::SerializeOutputSnafu {
+            version: T::get_version(),
+            output: "migration result",
         })
     }
 
@@ -211,18 +359,99 @@ impl<T: SettingsModel> Model for BottlerocketSetting<T> {
         &self,
         existing_partial: Option<serde_json::Value>,
         dependent_settings: Option<serde_json::Value>,
-    ) -> Result<GenerateResult<serde_json::Value, serde_json::Value>> {
-        let existing_partial = existing_partial.map(serde_json::from_value).transpose()?;
-
-        T::generate(existing_partial, dependent_settings).and_then(|gr| gr.erase_type())
+    ) -> error::Result<GenerateResult<serde_json::Value, serde_json::Value>> {
+        let existing_partial = existing_partial
+            .map(serde_json::from_value)
+            .transpose()
+            .context(error::DeserializeInputSnafu {
+                version: T::get_version(),
+                input: "existing partial",
+            })?;
+        let dependent_settings =
+            dependent_settings_for::<T>("generate", T::REQUIRED_SETTINGS, dependent_settings)?;
+
+        T::generate(existing_partial, dependent_settings)
+            .context(error::ModelCallbackFailedSnafu {
+                version: T::get_version(),
+                operation: "generate",
+            })?
+            .erase_type()
+            .context(error::SerializeOutputSnafu {
+                version: T::get_version(),
+                output: "generated value",
+            })
     }
 
     fn validate(
         &self,
         value: serde_json::Value,
         validated_settings: Option<serde_json::Value>,
-    ) -> Result<bool> {
-        let value = serde_json::from_value(value)?;
-        T::validate(value, validated_settings)
+    ) -> error::Result<ValidationReport> {
+        let value = serde_json::from_value(value).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "value",
+        })?;
+        let validated_settings =
+            dependent_settings_for::<T>("validate", T::VALIDATED_SETTINGS, validated_settings)?;
+        T::validate(value, validated_settings).context(error::ModelCallbackFailedSnafu {
+            version: T::get_version(),
+            operation: "validate",
+        })
+    }
+
+    fn validate_fields(&self, value: serde_json::Value) -> error::Result<ValidationReport> {
+        let value = serde_json::from_value(value).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "value",
+        })?;
+        Ok(T::validate_fields(&value))
+    }
+
+    fn normalize(&self, value: serde_json::Value) -> error::Result<serde_json::Value> {
+        let value: T = serde_json::from_value(value).context(error::DeserializeInputSnafu {
+            version: T::get_version(),
+            input: "value",
+        })?;
+        serde_json::to_value(value).context(error::SerializeOutputSnafu {
+            version: T::get_version(),
+            output: "value",
+        })
     }
 }
+
+/// Ensures that every declared dependency of a model is present in the provided settings, then
+/// deserializes them into the model's `DependentSettings`.
+fn dependent_settings_for<T: SettingsModel>(
+    operation: &'static str,
+    dependencies: &[SettingDependency],
+    provided: Option<serde_json::Value>,
+) -> error::Result<Option<T::DependentSettings>> {
+    let provided_names = provided.as_ref().and_then(serde_json::Value::as_object);
+    let missing: Vec<String> = dependencies
+        .iter()
+        .filter(|dependency| !provided_names.is_some_and(|p| p.contains_key(dependency.name)))
+        .map(|dependency| dependency.name.to_string())
+        .collect();
+
+    snafu::ensure!(
+        missing.is_empty(),
+        error::MissingDependentSettingsSnafu {
+            version: T::get_version(),
+            operation,
+            missing,
+        }
+    );
+
+    provided
+        .map(|provided| {
+            serde_path_to_error::deserialize(provided).or_else(|e| {
+                let path = e.path().to_string();
+                Err(e.into_inner()).context(error::MalformedDependentSettingsSnafu {
+                    version: T::get_version(),
+                    operation,
+                    path,
+                })
+            })
+        })
+        .transpose()
+}
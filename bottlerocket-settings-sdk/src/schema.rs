@@ -0,0 +1,11 @@
+//! JSON Schema generation for settings models, enabled by the `schemars` feature.
+//!
+//! Models which derive `schemars::JsonSchema` can publish their schema by returning
+//! `json_schema_for::<Self>()` from `SettingsModel::json_schema`, or by adding `schema` to their
+//! `#[settings(...)]` attribute.
+pub use schemars;
+
+/// Returns the JSON Schema describing values of the given type.
+pub fn json_schema_for<T: schemars::JsonSchema>() -> serde_json::Value {
+    schemars::schema_for!(T).to_value()
+}
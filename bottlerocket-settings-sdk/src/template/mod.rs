@@ -0,0 +1,79 @@
+//! Template helpers provided by settings extensions.
+//!
+//! Helpers follow handlebars semantics: a call such as `{{sample-helper "a" 2 key=true}}` passes
+//! the positional params `["a", 2]` and the hash `{"key": true}` to the helper, and the helper's
+//! output is written into the rendered template in place of the call.
+//!
+//! With the `handlebars` feature, a model version may also register a template, which the
+//! `render` command renders with a stored value and the version's helpers.
+#[cfg(feature = "handlebars")]
+mod render;
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "handlebars")]
+pub(crate) use render::render;
+
+/// Without the `handlebars` feature no templates can be registered, so none can be rendered.
+#[cfg(not(feature = "handlebars"))]
+pub(crate) fn render(
+    _: &crate::SettingsExtension,
+    version: &str,
+    _: &str,
+    _: &serde_json::Value,
+) -> crate::error::Result<String> {
+    crate::error::TemplateNotAvailableSnafu { version }.fail()
+}
+
+/// The arguments of a template helper call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HelperArgs {
+    /// The positional parameters of the call.
+    #[serde(default)]
+    pub params: Vec<serde_json::Value>,
+    /// The named `key=value` parameters of the call.
+    #[serde(default)]
+    pub hash: serde_json::Map<String, serde_json::Value>,
+}
+
+type HelperFn = dyn Fn(&HelperArgs) -> anyhow::Result<String> + Send + Sync;
+
+/// A named helper registered with `SettingsExtension::register_helper`.
+#[derive(Clone)]
+pub struct TemplateHelper {
+    name: String,
+    helper: Arc<HelperFn>,
+}
+
+impl TemplateHelper {
+    pub(crate) fn new(
+        name: String,
+        helper: impl Fn(&HelperArgs) -> anyhow::Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name,
+            helper: Arc::new(helper),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Invokes the helper, returning its rendered output.
+    pub(crate) fn call(&self, args: &HelperArgs) -> anyhow::Result<String> {
+        (self.helper)(args)
+    }
+}
+
+impl Debug for TemplateHelper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TemplateHelper")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
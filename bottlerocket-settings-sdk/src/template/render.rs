@@ -0,0 +1,99 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+    RenderErrorReason, TemplateError,
+};
+
+use super::{HelperArgs, TemplateHelper};
+use crate::error::{self, Result};
+use crate::SettingsExtension;
+
+/// Renders a value with the template registered for its version.
+///
+/// Templates are rendered in strict mode, so referencing a field which the value does not have is
+/// an error, and output is not HTML-escaped.
+pub(crate) fn render(
+    extension: &SettingsExtension,
+    version: &str,
+    template: &str,
+    value: &serde_json::Value,
+) -> Result<String> {
+    let mut registry = Handlebars::new();
+    registry.set_strict_mode(true);
+    registry.register_escape_fn(handlebars::no_escape);
+    for helper in extension.helpers(version) {
+        registry.register_helper(helper.name(), Box::new(HandlebarsHelper(helper.clone())));
+    }
+
+    registry
+        .register_template_string(version, template)
+        .map_err(|e| invalid_template(version, e))?;
+    registry
+        .render(version, value)
+        .map_err(|e| render_failed(version, e))
+}
+
+fn invalid_template(version: &str, e: TemplateError) -> error::Error {
+    let (line, column) = e.pos().unzip();
+    error::InvalidTemplateSnafu {
+        version,
+        line,
+        column,
+        reason: e.reason().to_string(),
+    }
+    .build()
+}
+
+fn render_failed(version: &str, e: RenderError) -> error::Error {
+    // A template which fails to parse reports its own position, rather than the render's.
+    if let RenderErrorReason::TemplateError(e) = e.reason() {
+        let (line, column) = e.pos().unzip();
+        return error::InvalidTemplateSnafu {
+            version,
+            line,
+            column,
+            reason: e.reason().to_string(),
+        }
+        .build();
+    }
+    let reason = match e.reason() {
+        // Report the failure of a helper as is.
+        RenderErrorReason::NestedError(source) => source.to_string(),
+        reason => reason.to_string(),
+    };
+    error::RenderTemplateSnafu {
+        version,
+        line: e.line_no,
+        column: e.column_no,
+        reason,
+    }
+    .build()
+}
+
+/// Adapts a registered helper to handlebars, passing it the evaluated params and hash of the call.
+struct HandlebarsHelper(TemplateHelper);
+
+impl HelperDef for HandlebarsHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let args = HelperArgs {
+            params: h.params().iter().map(|p| p.value().clone()).collect(),
+            hash: h
+                .hash()
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.value().clone()))
+                .collect(),
+        };
+        let rendered = self
+            .0
+            .call(&args)
+            .map_err(|e| RenderErrorReason::NestedError(e.into()))?;
+        out.write(&rendered)?;
+        Ok(())
+    }
+}
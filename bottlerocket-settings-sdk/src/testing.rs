@@ -0,0 +1,185 @@
+//! Helpers for testing a `SettingsExtension` in-process, by running its proto1 commands with
+//! `SettingsExtension::invoke` rather than building and executing the extension's binary.
+//! Requires the `testing` feature, which extensions usually enable only in `[dev-dependencies]`.
+//!
+//! The `assert_*` helpers panic with the extension's error report if a command does not behave as
+//! expected. Values are passed as anything which serializes to JSON; optional inputs, such as the
+//! current value of a setting, are omitted if they serialize to `null`.
+//!
+//! ```ignore
+//! use bottlerocket_settings_sdk::testing;
+//!
+//! let extension = SettingsExtension::with_models(vec![BottlerocketSetting::<MotdV1>::model()]);
+//! let motd = MotdV1 { motd: "hello".to_string() };
+//! testing::assert_set(&extension, "v1", None::<MotdV1>, &motd, &motd);
+//! testing::assert_valid(&extension, "v1", &motd);
+//! ```
+use serde::Serialize;
+
+use crate::{ErrorKind, ErrorReport, GenerateResult, Output, SettingsExtension, ValidationReport};
+
+/// Runs an extension command in-process, given the arguments which would follow the program name.
+pub fn invoke(extension: &SettingsExtension, args: &[&str]) -> Output {
+    invoke_with_stdin(extension, args, "")
+}
+
+/// Runs an extension command in-process like `invoke`, with the given stdin.
+pub fn invoke_with_stdin(extension: &SettingsExtension, args: &[&str], stdin: &str) -> Output {
+    let argv = std::iter::once("settings-extension").chain(args.iter().copied());
+    extension.invoke_with_stdin(argv, stdin.as_bytes())
+}
+
+/// Asserts that a command succeeded, returning its JSON output.
+#[track_caller]
+pub fn assert_succeeds(output: &Output) -> serde_json::Value {
+    assert!(
+        output.success(),
+        "command failed with exit code {}: {}",
+        output.exit_code,
+        output.stderr
+    );
+    output
+        .json()
+        .unwrap_or_else(|e| panic!("command output is not JSON: {}: {}", e, output.stdout))
+}
+
+/// Asserts that a command failed with the given kind of error, returning its error report.
+#[track_caller]
+pub fn assert_fails(output: &Output, kind: ErrorKind) -> ErrorReport {
+    assert!(
+        !output.success(),
+        "command succeeded, but was expected to fail: {}",
+        output.stdout
+    );
+    let report = output
+        .error_report()
+        .unwrap_or_else(|| panic!("command did not write an error report: {}", output.stderr));
+    assert_eq!(report.kind, kind, "unexpected error: {}", report.message);
+    assert_eq!(output.exit_code, kind.exit_code());
+    report
+}
+
+/// Asserts that setting `value` over `current_value` results in `expected`.
+#[track_caller]
+pub fn assert_set(
+    extension: &SettingsExtension,
+    version: &str,
+    current_value: impl Serialize,
+    value: impl Serialize,
+    expected: impl Serialize,
+) {
+    let mut args = vec![
+        "proto1".to_string(),
+        "set".to_string(),
+        format!("--setting-version={}", version),
+        format!("--value={}", to_json(value)),
+    ];
+    args.extend(optional_arg("current-value", current_value));
+    let output = invoke(extension, &as_strs(&args));
+    assert_eq!(assert_succeeds(&output), to_value(expected));
+}
+
+/// Asserts that generating a value from `existing_partial` results in `expected`.
+#[track_caller]
+pub fn assert_generates<P, C>(
+    extension: &SettingsExtension,
+    version: &str,
+    existing_partial: impl Serialize,
+    expected: GenerateResult<P, C>,
+) where
+    P: Serialize,
+    C: Serialize,
+{
+    let mut args = vec![
+        "proto1".to_string(),
+        "generate".to_string(),
+        format!("--setting-version={}", version),
+    ];
+    args.extend(optional_arg("existing-partial", existing_partial));
+    let output = invoke(extension, &as_strs(&args));
+    assert_eq!(assert_succeeds(&output), to_value(expected));
+}
+
+/// Asserts that `value` passes validation, returning the report so that warnings can be checked.
+#[track_caller]
+pub fn assert_valid(
+    extension: &SettingsExtension,
+    version: &str,
+    value: impl Serialize,
+) -> ValidationReport {
+    let report = validate(extension, version, value);
+    assert!(report.is_valid(), "value is invalid: {:?}", report.issues());
+    report
+}
+
+/// Asserts that `value` fails validation, returning the report so that its issues can be checked.
+#[track_caller]
+pub fn assert_invalid(
+    extension: &SettingsExtension,
+    version: &str,
+    value: impl Serialize,
+) -> ValidationReport {
+    let report = validate(extension, version, value);
+    assert!(!report.is_valid(), "value is unexpectedly valid");
+    report
+}
+
+/// Asserts that migrating `value` from `from_version` to `target_version` results in `expected`.
+#[track_caller]
+pub fn assert_migrates(
+    extension: &SettingsExtension,
+    value: impl Serialize,
+    from_version: &str,
+    target_version: &str,
+    expected: impl Serialize,
+) {
+    let args = [
+        "proto1".to_string(),
+        "migrate".to_string(),
+        format!("--value={}", to_json(value)),
+        format!("--from-version={}", from_version),
+        format!("--target-version={}", target_version),
+    ];
+    let output = invoke(extension, &as_strs(&args));
+    assert_eq!(assert_succeeds(&output), to_value(expected));
+}
+
+#[track_caller]
+fn validate(
+    extension: &SettingsExtension,
+    version: &str,
+    value: impl Serialize,
+) -> ValidationReport {
+    let args = [
+        "proto1".to_string(),
+        "validate".to_string(),
+        format!("--setting-version={}", version),
+        format!("--value={}", to_json(value)),
+    ];
+    let output = invoke(extension, &as_strs(&args));
+    serde_json::from_value(assert_succeeds(&output))
+        .unwrap_or_else(|e| panic!("validate did not return a validation report: {}", e))
+}
+
+#[track_caller]
+fn to_value(value: impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_else(|e| panic!("failed to serialize value: {}", e))
+}
+
+#[track_caller]
+fn to_json(value: impl Serialize) -> String {
+    to_value(value).to_string()
+}
+
+/// Returns the `--name=value` argument for an optional input, or nothing if it is `null`.
+#[track_caller]
+fn optional_arg(name: &str, value: impl Serialize) -> Option<String> {
+    match to_value(value) {
+        serde_json::Value::Null => None,
+        value => Some(format!("--{}={}", name, value)),
+    }
+}
+
+fn as_strs(args: &[String]) -> Vec<&str> {
+    args.iter().map(String::as_str).collect()
+}
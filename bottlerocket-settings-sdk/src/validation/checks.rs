@@ -0,0 +1,326 @@
+//! Common checks against a single value.
+//!
+//! Each check returns `Ok(())` if the value passes, or a message describing the problem. Checks
+//! can be chained with `Result::and_then`, and recorded with `ValidationReport::check`.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::ops::RangeBounds;
+
+/// The result of a check: `Ok(())`, or a message explaining why the value is invalid.
+pub type CheckResult = std::result::Result<(), String>;
+
+/// Values with a length, which can be checked with `min_len`, `max_len` and `len_in`.
+///
+/// The length of a string is its number of characters.
+pub trait Length {
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> Length for BTreeMap<K, V> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V, S> Length for HashMap<K, V, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for BTreeSet<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, S> Length for HashSet<T, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Length + ?Sized> Length for &T {
+    fn length(&self) -> usize {
+        (**self).length()
+    }
+}
+
+/// Checks that a value has a length of at least `min`.
+pub fn min_len<V: Length + ?Sized>(value: &V, min: usize) -> CheckResult {
+    let len = value.length();
+    if len < min {
+        return Err(format!(
+            "length {} is shorter than the minimum of {}",
+            len, min
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a value has a length of at most `max`.
+pub fn max_len<V: Length + ?Sized>(value: &V, max: usize) -> CheckResult {
+    let len = value.length();
+    if len > max {
+        return Err(format!(
+            "length {} is longer than the maximum of {}",
+            len, max
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the length of a value lies within the given range.
+pub fn len_in<V, R>(value: &V, range: R) -> CheckResult
+where
+    V: Length + ?Sized,
+    R: RangeBounds<usize> + Debug,
+{
+    let len = value.length();
+    if !range.contains(&len) {
+        return Err(format!(
+            "length {} is outside of the range {:?}",
+            len, range
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a value lies within the given range, e.g. `checks::range(&port, 1..=65535)`.
+pub fn range<T, R>(value: &T, range: R) -> CheckResult
+where
+    T: PartialOrd + Debug,
+    R: RangeBounds<T> + Debug,
+{
+    if !range.contains(value) {
+        return Err(format!("{:?} is outside of the range {:?}", value, range));
+    }
+    Ok(())
+}
+
+/// Checks that a value is one of the allowed values.
+pub fn one_of<T, U>(value: &T, allowed: &[U]) -> CheckResult
+where
+    T: PartialEq<U> + Debug + ?Sized,
+    U: Debug,
+{
+    if !allowed.iter().any(|candidate| value == candidate) {
+        return Err(format!("{:?} is not one of {:?}", value, allowed));
+    }
+    Ok(())
+}
+
+/// Checks that a string matches a regular expression. The pattern is not implicitly anchored.
+///
+/// An invalid pattern fails the check.
+pub fn pattern(value: &str, pattern: &str) -> CheckResult {
+    let regex = regex::Regex::new(pattern).map_err(|e| {
+        format!(
+            "pattern '{}' is not a valid regular expression: {}",
+            pattern, e
+        )
+    })?;
+    matches(value, &regex)
+}
+
+/// Checks that a string matches a compiled regular expression.
+pub fn matches(value: &str, regex: &regex::Regex) -> CheckResult {
+    if !regex.is_match(value) {
+        return Err(format!(
+            "'{}' does not match the pattern '{}'",
+            value,
+            regex.as_str()
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that a string is an absolute URL.
+pub fn url(value: &str) -> CheckResult {
+    url::Url::parse(value)
+        .map(|_| ())
+        .map_err(|e| format!("'{}' is not a valid URL: {}", value, e))
+}
+
+/// Checks that a string is a valid hostname, per RFC 1123.
+pub fn hostname(value: &str) -> CheckResult {
+    let invalid = |reason: &str| Err(format!("'{}' is not a valid hostname: {}", value, reason));
+
+    let name = value.strip_suffix('.').unwrap_or(value);
+    if name.is_empty() {
+        return invalid("it is empty");
+    }
+    if name.len() > 253 {
+        return invalid("it is longer than 253 characters");
+    }
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return invalid("each label must be between 1 and 63 characters");
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return invalid("labels may only contain ASCII letters, digits and hyphens");
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return invalid("labels may not begin or end with a hyphen");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        assert!(min_len("ab", 2).is_ok());
+        assert_eq!(
+            min_len("a", 2),
+            Err("length 1 is shorter than the minimum of 2".to_string())
+        );
+        assert!(max_len(&vec![1, 2], 2).is_ok());
+        assert_eq!(
+            max_len(&vec![1, 2, 3], 2),
+            Err("length 3 is longer than the maximum of 2".to_string())
+        );
+        // Strings are measured in characters, rather than bytes.
+        assert!(max_len("héllo", 5).is_ok());
+    }
+
+    #[test]
+    fn len_in_bounds() {
+        assert!(len_in("a", 1..3).is_ok());
+        assert!(len_in("ab", 1..3).is_ok());
+        assert_eq!(
+            len_in("abc", 1..3),
+            Err("length 3 is outside of the range 1..3".to_string())
+        );
+        assert!(len_in("abc", 1..=3).is_ok());
+        assert!(len_in("", 1..=3).is_err());
+        assert!(len_in(&HashSet::from([1]), ..1).is_err());
+    }
+
+    #[test]
+    fn range_bounds() {
+        assert!(range(&1, 1..=65535).is_ok());
+        assert!(range(&65535, 1..=65535).is_ok());
+        assert_eq!(
+            range(&0, 1..=65535),
+            Err("0 is outside of the range 1..=65535".to_string())
+        );
+        assert!(range(&65536, 1..=65535).is_err());
+        assert!(range(&0.5, 0.0..1.0).is_ok());
+        assert!(range(&1.0, 0.0..1.0).is_err());
+    }
+
+    #[test]
+    fn one_of_values() {
+        assert!(one_of(&"debug", &["debug", "info"]).is_ok());
+        assert_eq!(
+            one_of(&"trace", &["debug", "info"]),
+            Err(r#""trace" is not one of ["debug", "info"]"#.to_string())
+        );
+        assert!(one_of(&3, &[1, 2, 3]).is_ok());
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(pattern("v12", "^v[0-9]+$").is_ok());
+        assert_eq!(
+            pattern("v1.2", "^v[0-9]+$"),
+            Err("'v1.2' does not match the pattern '^v[0-9]+$'".to_string())
+        );
+        // Patterns are not anchored.
+        assert!(pattern("release-v1", "v[0-9]").is_ok());
+        assert!(pattern("v1", "(")
+            .unwrap_err()
+            .starts_with("pattern '(' is not a valid regular expression"));
+
+        let regex = regex::Regex::new("^[a-z]+$").unwrap();
+        assert!(matches("motd", &regex).is_ok());
+        assert!(matches("MOTD", &regex).is_err());
+    }
+
+    #[test]
+    fn urls() {
+        assert!(url("https://example.com/path?query").is_ok());
+        assert!(url("file:///etc/motd").is_ok());
+        assert!(url("example.com")
+            .unwrap_err()
+            .starts_with("'example.com' is not a valid URL"));
+        assert!(url("").is_err());
+    }
+
+    #[test]
+    fn hostnames() {
+        for valid in [
+            "localhost",
+            "host-1.example.com",
+            "example.com.",
+            "a",
+            "1.2.3.4",
+        ] {
+            assert!(hostname(valid).is_ok(), "{} was rejected", valid);
+        }
+        assert!(hostname(&format!("{}.com", "a".repeat(63))).is_ok());
+
+        let invalid = [
+            ("", "it is empty"),
+            (".", "it is empty"),
+            (
+                "host..example",
+                "each label must be between 1 and 63 characters",
+            ),
+            (
+                "host_1",
+                "labels may only contain ASCII letters, digits and hyphens",
+            ),
+            (
+                "hôst",
+                "labels may only contain ASCII letters, digits and hyphens",
+            ),
+            ("-host", "labels may not begin or end with a hyphen"),
+            ("host-.example", "labels may not begin or end with a hyphen"),
+        ];
+        for (value, reason) in invalid {
+            assert_eq!(
+                hostname(value),
+                Err(format!("'{}' is not a valid hostname: {}", value, reason))
+            );
+        }
+
+        let long_label = format!("{}.com", "a".repeat(64));
+        assert!(hostname(&long_label)
+            .unwrap_err()
+            .ends_with("each label must be between 1 and 63 characters"));
+        let long_name = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(long_name.len(), 255);
+        assert!(hostname(&long_name)
+            .unwrap_err()
+            .ends_with("it is longer than 253 characters"));
+    }
+}
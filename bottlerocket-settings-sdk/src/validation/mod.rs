@@ -0,0 +1,224 @@
+//! Reports describing whether a settings value is valid, and checks for building them.
+//!
+//! `SettingsModel::validate` returns a `ValidationReport`, which lists every problem found with a
+//! value along with the path of the field it was found in. The `checks` module provides common
+//! checks whose results can be recorded in a report:
+//!
+//! ```
+//! use bottlerocket_settings_sdk::validation::{checks, ValidationReport};
+//!
+//! let motd = "Hello!";
+//! let mut report = ValidationReport::new();
+//! report.check("motd", checks::max_len(motd, 255));
+//! report.check("motd", checks::pattern(motd, "^[[:print:]]*$"));
+//! assert!(report.is_valid());
+//! ```
+pub mod checks;
+
+use std::fmt::Display;
+
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+pub use checks::CheckResult;
+
+/// The severity of a `ValidationIssue`. Only errors cause a value to be rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValidationSeverity {
+    Warning,
+    Error,
+}
+
+impl Display for ValidationSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationSeverity::Warning => write!(f, "warning"),
+            ValidationSeverity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found while validating a value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ValidationIssue {
+    /// The path of the offending field, e.g. `motd` or `servers[2].url`. Empty if the issue
+    /// applies to the value as a whole.
+    pub field: String,
+    pub message: String,
+    pub severity: ValidationSeverity,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.severity, self.field, self.message)
+        }
+    }
+}
+
+/// The outcome of validating a settings value.
+///
+/// A report is serialized with a `valid` field for the benefit of its readers. The field is derived
+/// from the report's issues, and is ignored when a report is deserialized.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl Serialize for ValidationReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("ValidationReport", 2)?;
+        report.serialize_field("valid", &self.is_valid())?;
+        report.serialize_field("issues", &self.issues)?;
+        report.end()
+    }
+}
+
+impl Default for ValidationReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValidationReport {
+    /// Creates a report with no issues.
+    pub fn new() -> Self {
+        Self { issues: Vec::new() }
+    }
+
+    /// Returns whether the report contains no errors. Warnings do not make a value invalid.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns every issue found, in the order they were recorded.
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Records an issue with the given field.
+    pub fn push(
+        &mut self,
+        field: impl Into<String>,
+        severity: ValidationSeverity,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        self.issues.push(ValidationIssue {
+            field: field.into(),
+            message: message.into(),
+            severity,
+        });
+        self
+    }
+
+    /// Records an error with the given field.
+    pub fn error(&mut self, field: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.push(field, ValidationSeverity::Error, message)
+    }
+
+    /// Records a warning about the given field.
+    pub fn warning(&mut self, field: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.push(field, ValidationSeverity::Warning, message)
+    }
+
+    /// Records an error with the given field if the check failed.
+    pub fn check(&mut self, field: impl Into<String>, result: CheckResult) -> &mut Self {
+        if let Err(message) = result {
+            self.error(field, message);
+        }
+        self
+    }
+
+    /// Records a warning about the given field if the check failed.
+    pub fn check_warning(&mut self, field: impl Into<String>, result: CheckResult) -> &mut Self {
+        if let Err(message) = result {
+            self.warning(field, message);
+        }
+        self
+    }
+
+    /// Adds the issues of another report, such as one for a nested value, to this report.
+    ///
+    /// The field paths of the merged issues are prefixed with `prefix`, if it is not empty.
+    pub fn merge(&mut self, prefix: &str, other: ValidationReport) -> &mut Self {
+        for issue in other.issues {
+            let field = match (prefix.is_empty(), issue.field.is_empty()) {
+                (true, _) => issue.field,
+                (false, true) => prefix.to_string(),
+                (false, false) if issue.field.starts_with('[') => {
+                    format!("{}{}", prefix, issue.field)
+                }
+                (false, false) => format!("{}.{}", prefix, issue.field),
+            };
+            self.push(field, issue.severity, issue.message);
+        }
+        self
+    }
+
+    /// Returns the issues which make the value invalid.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues_with(ValidationSeverity::Error)
+    }
+
+    /// Returns the issues which do not make the value invalid.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues_with(ValidationSeverity::Warning)
+    }
+
+    fn issues_with(&self, severity: ValidationSeverity) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validity_follows_issues() {
+        let mut report = ValidationReport::new();
+        assert!(report.is_valid());
+        report.warning("motd", "is long");
+        assert!(report.is_valid());
+        report.error("motd", "is empty");
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn serializes_validity() {
+        let mut report = ValidationReport::new();
+        report.error("motd", "is empty");
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "valid": false,
+                "issues": [{"field": "motd", "message": "is empty", "severity": "error"}],
+            })
+        );
+    }
+
+    #[test]
+    fn deserialized_validity_follows_issues() {
+        let report: ValidationReport = serde_json::from_value(json!({
+            "valid": true,
+            "issues": [{"field": "motd", "message": "is empty", "severity": "error"}],
+        }))
+        .unwrap();
+        assert!(!report.is_valid());
+
+        let report: ValidationReport = serde_json::from_value(json!({
+            "valid": false,
+            "issues": [],
+        }))
+        .unwrap();
+        assert!(report.is_valid());
+    }
+}
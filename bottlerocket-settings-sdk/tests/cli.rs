@@ -0,0 +1,32 @@
+use bottlerocket_settings_sdk::cli::{Cli, Command, ExtensionCommand, Parser, Protocol};
+use clap::CommandFactory;
+
+fn parse(args: &[&str]) -> Command {
+    let argv = std::iter::once("settings-extension").chain(args.iter().copied());
+    Cli::try_parse_from(argv).unwrap().command
+}
+
+#[test]
+fn definition() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn protocols_and_extension_commands_are_top_level() {
+    assert!(matches!(
+        parse(&["proto1", "schema", "--setting-version=v1"]),
+        Command::Protocol(Protocol::Proto1(_))
+    ));
+    assert!(matches!(
+        parse(&["proto2", "schema", "--setting-version=v1"]),
+        Command::Protocol(Protocol::Proto2(_))
+    ));
+    assert!(matches!(
+        parse(&["describe"]),
+        Command::Extension(ExtensionCommand::Describe)
+    ));
+    assert!(matches!(
+        parse(&["negotiate", "--accept=proto2,proto1"]),
+        Command::Extension(ExtensionCommand::Negotiate(n)) if n.accept == ["proto2", "proto1"]
+    ));
+}
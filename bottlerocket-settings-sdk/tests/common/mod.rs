@@ -0,0 +1,53 @@
+//! Models shared by the integration tests.
+#![allow(dead_code)]
+
+use anyhow::Result;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, SettingsExtension, SettingsModel, SettingsPartial,
+};
+use serde::{Deserialize, Serialize};
+
+/// A setting with a single version.
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1")]
+pub struct Motd {
+    pub motd: String,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v1", forward = MotdV2, migrate_forward = to_v2)]
+pub struct MotdV1 {
+    pub motd: String,
+}
+
+pub fn to_v2(value: MotdV1) -> Result<MotdV2> {
+    Ok(MotdV2 {
+        motd: value.motd,
+        author: "unknown".to_string(),
+    })
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(version = "v2", backward = MotdV1, migrate_backward = to_v1)]
+pub struct MotdV2 {
+    #[settings(validate(min_len = 1))]
+    pub motd: String,
+    pub author: String,
+}
+
+pub fn to_v1(value: MotdV2) -> Result<MotdV1> {
+    Ok(MotdV1 { motd: value.motd })
+}
+
+/// An extension with the single version of `Motd`.
+pub fn motd_extension() -> SettingsExtension {
+    SettingsExtension::with_models(vec![BottlerocketSetting::<Motd>::model()])
+}
+
+/// An extension with `MotdV1` and `MotdV2`, which migrate to each other.
+pub fn motd_v1_v2_extension() -> SettingsExtension {
+    SettingsExtension::with_models(vec![
+        BottlerocketSetting::<MotdV1>::model(),
+        BottlerocketSetting::<MotdV2>::model(),
+    ])
+}
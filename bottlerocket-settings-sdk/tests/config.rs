@@ -0,0 +1,76 @@
+mod common;
+
+use bottlerocket_settings_sdk::config::{ConfigParser, ExtensionConfig};
+use bottlerocket_settings_sdk::{testing, Error, ErrorKind, ModelDiagnostic};
+use common::MotdV1;
+
+fn config(supported_versions: &str) -> ExtensionConfig {
+    ExtensionConfig::from_toml(&format!(
+        "[extension]\nname = \"motd\"\nsupported-versions = {}\ndefault-version = \"v1\"\n",
+        supported_versions
+    ))
+    .unwrap()
+}
+
+fn config_diagnostics(supported_versions: &str) -> Vec<ModelDiagnostic> {
+    match common::motd_v1_v2_extension().check_config(&config(supported_versions)) {
+        Ok(()) => Vec::new(),
+        Err(Error::InconsistentModels { diagnostics }) => diagnostics,
+        Err(e) => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn config_matches_models() {
+    assert_eq!(config_diagnostics(r#"["v1", "v2"]"#), []);
+}
+
+#[test]
+fn config_version_not_registered() {
+    assert_eq!(
+        config_diagnostics(r#"["v1", "v2", "v3"]"#),
+        [ModelDiagnostic::ConfigVersionNotRegistered {
+            version: "v3".to_string()
+        }]
+    );
+}
+
+#[test]
+fn version_missing_from_config() {
+    assert_eq!(
+        config_diagnostics(r#"["v1"]"#),
+        [ModelDiagnostic::VersionMissingFromConfig {
+            version: "v2".to_string()
+        }]
+    );
+}
+
+#[test]
+fn generate_config() {
+    let extension = common::motd_v1_v2_extension()
+        .register_helper::<MotdV1>("sample-helper", |_| Ok(String::new()));
+    let output = testing::invoke(
+        &extension,
+        &["generate-config", "--name=motd", "--default-version=v2"],
+    );
+    assert!(output.success(), "{}", output.stderr);
+
+    let config = ConfigParser::new()
+        .with_known_helpers(["sample-helper"])
+        .parse(&output.stdout)
+        .unwrap();
+    assert_eq!(config.extension.name, "motd");
+    assert_eq!(config.extension.supported_versions, ["v1", "v2"]);
+    assert_eq!(config.extension.default_version, "v2");
+    assert_eq!(config.templating["v1"].helpers, ["sample-helper"]);
+    extension.check_config(&config).unwrap();
+}
+
+#[test]
+fn generate_config_unknown_default() {
+    let output = testing::invoke(
+        &common::motd_v1_v2_extension(),
+        &["generate-config", "--name=motd", "--default-version=v3"],
+    );
+    testing::assert_fails(&output, ErrorKind::UnknownVersion);
+}
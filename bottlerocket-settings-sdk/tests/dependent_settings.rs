@@ -0,0 +1,81 @@
+use anyhow::Result;
+use bottlerocket_settings_sdk::{
+    testing, BottlerocketSetting, Error, ErrorKind, GenerateResult, Output, SettingsExtension,
+    SettingsModel, SettingsPartial,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(
+    version = "v1",
+    generate = generate_motd,
+    dependent_settings = Dependencies,
+    required_setting(name = "network", version = "v1")
+)]
+struct Motd {
+    motd: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Dependencies {
+    network: Network,
+}
+
+#[derive(Deserialize, Debug)]
+struct Network {
+    hostname: String,
+}
+
+fn generate_motd(
+    _: Option<MotdPartial>,
+    dependencies: Option<Dependencies>,
+) -> Result<GenerateResult<MotdPartial, Motd>> {
+    let hostname = dependencies.unwrap().network.hostname;
+    Ok(GenerateResult::Complete(Some(Motd {
+        motd: format!("welcome to {}", hostname),
+    })))
+}
+
+fn generate(required_settings: serde_json::Value) -> Output {
+    let extension = SettingsExtension::with_models(vec![BottlerocketSetting::<Motd>::model()]);
+    let arg = format!("--required-settings={}", required_settings);
+    testing::invoke(
+        &extension,
+        &["proto1", "generate", "--setting-version=v1", &arg],
+    )
+}
+
+#[test]
+fn generates_from_dependencies() {
+    let output = generate(json!({"network": {"hostname": "host-1"}}));
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({"Complete": {"motd": "welcome to host-1"}})
+    );
+}
+
+#[test]
+fn missing_dependency() {
+    let output = generate(json!({"ntp": {}}));
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    let expected = Error::MissingDependentSettings {
+        version: "v1".to_string(),
+        operation: "generate",
+        missing: vec!["network".to_string()],
+    };
+    assert_eq!(report.message, expected.to_string());
+    assert_eq!(report.setting_version.as_deref(), Some("v1"));
+}
+
+#[test]
+fn malformed_dependency() {
+    let output = generate(json!({"network": {"hostname": 7}}));
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    assert!(
+        report.message.contains("'network.hostname'"),
+        "{}",
+        report.message
+    );
+    assert_eq!(report.causes.len(), 1);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use bottlerocket_settings_sdk::{testing, SettingsExtension};
+use common::MotdV1;
+use serde_json::json;
+
+fn extension() -> SettingsExtension {
+    common::motd_v1_v2_extension().register_helper::<MotdV1>("shout", |_| Ok(String::new()))
+}
+
+fn operations(extension: &SettingsExtension, version: &str) -> Vec<&'static str> {
+    let description = extension.describe();
+    description
+        .versions
+        .into_iter()
+        .find(|v| v.version == version)
+        .unwrap()
+        .operations
+}
+
+#[test]
+fn describe() {
+    let output = testing::invoke(&extension(), &["describe"]);
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({
+            "protocols": ["proto1", "proto2"],
+            "versions": [
+                {
+                    "version": "v1",
+                    "migrates-forward-to": "v2",
+                    "migrates-backward-to": null,
+                    "neighbours": ["v2"],
+                    "required-settings": [],
+                    "validated-settings": [],
+                    "helpers": ["shout"],
+                    "operations": ["set", "generate", "validate", "migrate", "migrate-batch", "helper"],
+                },
+                {
+                    "version": "v2",
+                    "migrates-forward-to": null,
+                    "migrates-backward-to": "v1",
+                    "neighbours": ["v1"],
+                    "required-settings": [],
+                    "validated-settings": [],
+                    "helpers": [],
+                    "operations": ["set", "generate", "validate", "migrate", "migrate-batch"],
+                },
+            ],
+            "migrations": [
+                {"from": "v1", "to": "v2", "direction": "forward"},
+                {"from": "v2", "to": "v1", "direction": "backward"},
+            ],
+        })
+    );
+}
+
+#[test]
+fn describe_helper() {
+    assert!(operations(&extension(), "v1").contains(&"helper"));
+    assert!(!operations(&common::motd_v1_v2_extension(), "v1").contains(&"helper"));
+}
+
+#[cfg(feature = "handlebars")]
+#[test]
+fn describe_render() {
+    let extension = extension().register_template::<common::MotdV2>("{{motd}}");
+    assert!(operations(&extension, "v2").contains(&"render"));
+    assert!(!operations(&extension, "v1").contains(&"render"));
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn describe_schema() {
+    use bottlerocket_settings_sdk::schema::schemars::JsonSchema;
+    use bottlerocket_settings_sdk::{BottlerocketSetting, SettingsModel, SettingsPartial};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(SettingsModel, SettingsPartial, JsonSchema, Serialize, Deserialize, Debug)]
+    #[settings(version = "v1", schema)]
+    #[schemars(crate = "bottlerocket_settings_sdk::schema::schemars")]
+    struct Banner {
+        text: String,
+    }
+
+    let extension = SettingsExtension::with_models(vec![BottlerocketSetting::<Banner>::model()]);
+    assert!(operations(&extension, "v1").contains(&"schema"));
+    assert!(!operations(&common::motd_extension(), "v1").contains(&"schema"));
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use anyhow::Result;
+use bottlerocket_settings_sdk::migrate::MigrationDirection;
+use bottlerocket_settings_sdk::{
+    BottlerocketSetting, DiagnosticSeverity, Error, ModelDiagnostic, SettingsExtension,
+    SettingsModel, SettingsPartial,
+};
+use common::{Motd, MotdV1, MotdV2};
+use serde::{Deserialize, Serialize};
+
+/// A branch which migrates backward into v1, although v1 migrates forward into v2.
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v1-lts", backward = MotdV1, migrate_backward = lts_to_v1)]
+struct MotdLts {
+    motd: String,
+}
+
+fn lts_to_v1(value: MotdLts) -> Result<MotdV1> {
+    Ok(MotdV1 { motd: value.motd })
+}
+
+fn v1_to_lts(value: MotdV1) -> Result<MotdLts> {
+    Ok(MotdLts { motd: value.motd })
+}
+
+/// A second version of `Motd` with no migrations.
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v2")]
+struct Standalone {
+    motd: String,
+}
+
+#[test]
+fn consistent() {
+    let extension = common::motd_v1_v2_extension();
+    assert_eq!(extension.model_diagnostics(), []);
+    assert!(extension.validated().is_ok());
+}
+
+#[test]
+fn duplicate_version() {
+    let extension = SettingsExtension::with_models(vec![
+        BottlerocketSetting::<Motd>::model(),
+        BottlerocketSetting::<Motd>::model(),
+    ]);
+    assert_eq!(
+        extension.model_diagnostics(),
+        [ModelDiagnostic::DuplicateVersion {
+            version: "v1".to_string()
+        }]
+    );
+}
+
+#[test]
+fn dangling_migration_target() {
+    let extension = SettingsExtension::with_models(vec![BottlerocketSetting::<MotdV1>::model()]);
+    assert_eq!(
+        extension.model_diagnostics(),
+        [ModelDiagnostic::DanglingMigrationTarget {
+            version: "v1".to_string(),
+            direction: MigrationDirection::Forward,
+            target: "v2".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn dangling_migration_source() {
+    let extension = common::motd_v1_v2_extension().register_migration::<MotdLts, MotdV2>(|value| {
+        Ok(MotdV2 {
+            motd: value.motd,
+            author: "lts".to_string(),
+        })
+    });
+    assert_eq!(
+        extension.model_diagnostics(),
+        [ModelDiagnostic::DanglingMigrationSource {
+            version: "v1-lts".to_string(),
+            direction: MigrationDirection::Registered,
+            target: "v2".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn asymmetric_migration() {
+    let extension = SettingsExtension::with_models(vec![
+        BottlerocketSetting::<MotdV1>::model(),
+        BottlerocketSetting::<MotdV2>::model(),
+        BottlerocketSetting::<MotdLts>::model(),
+    ])
+    .register_migration::<MotdV1, MotdLts>(v1_to_lts);
+    let diagnostics = extension.model_diagnostics();
+    assert_eq!(
+        diagnostics,
+        [ModelDiagnostic::AsymmetricMigration {
+            version: "v1-lts".to_string(),
+            direction: MigrationDirection::Backward,
+            target: "v1".to_string(),
+            target_migrates_to: Some("v2".to_string()),
+        }]
+    );
+    assert_eq!(diagnostics[0].severity(), DiagnosticSeverity::Warning);
+
+    // Warnings do not stop an extension from starting.
+    assert!(extension.validated().is_ok());
+}
+
+#[test]
+fn unreachable_version() {
+    let extension = SettingsExtension::with_models(vec![
+        BottlerocketSetting::<Motd>::model(),
+        BottlerocketSetting::<Standalone>::model(),
+    ]);
+    assert_eq!(
+        extension.model_diagnostics(),
+        [
+            ModelDiagnostic::UnreachableVersion {
+                version: "v1".to_string(),
+                unreachable_from: vec!["v2".to_string()],
+            },
+            ModelDiagnostic::UnreachableVersion {
+                version: "v2".to_string(),
+                unreachable_from: vec!["v1".to_string()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn try_with_models_rejects_errors() {
+    // Without the migration into the branch, the branch cannot be reached, which is an error. The
+    // asymmetric migration is only a warning, and is not reported.
+    let error = SettingsExtension::try_with_models(vec![
+        BottlerocketSetting::<MotdV1>::model(),
+        BottlerocketSetting::<MotdV2>::model(),
+        BottlerocketSetting::<MotdLts>::model(),
+    ])
+    .unwrap_err();
+    match error {
+        Error::InconsistentModels { diagnostics } => assert_eq!(
+            diagnostics,
+            [ModelDiagnostic::UnreachableVersion {
+                version: "v1-lts".to_string(),
+                unreachable_from: vec!["v1".to_string(), "v2".to_string()],
+            }]
+        ),
+        error => panic!("unexpected error: {}", error),
+    }
+
+    assert!(SettingsExtension::try_with_models(vec![
+        BottlerocketSetting::<MotdV1>::model(),
+        BottlerocketSetting::<MotdV2>::model(),
+    ])
+    .is_ok());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use bottlerocket_settings_sdk::{testing, ErrorKind, Output, SettingsExtension};
+use common::Motd;
+use serde_json::json;
+
+fn extension() -> SettingsExtension {
+    common::motd_extension()
+        .register_helper::<Motd>("join", |args| {
+            let separator = args.hash.get("sep").and_then(|s| s.as_str()).unwrap_or(" ");
+            let params: Vec<String> = args.params.iter().map(|p| p.to_string()).collect();
+            Ok(params.join(separator))
+        })
+        .register_helper::<Motd>("broken", |_| anyhow::bail!("the helper is broken"))
+}
+
+fn invoke_helper(name: &str, version: &str) -> Output {
+    testing::invoke(
+        &extension(),
+        &[
+            "proto1",
+            "helper",
+            &format!("--setting-version={}", version),
+            &format!("--name={}", name),
+            r#"--args={"params": [1, "a"], "hash": {"sep": "-"}}"#,
+        ],
+    )
+}
+
+#[test]
+fn calls_helper() {
+    let output = invoke_helper("join", "v1");
+    assert_eq!(testing::assert_succeeds(&output), json!(r#"1-"a""#));
+
+    // Both the params and the hash may be omitted.
+    let output = testing::invoke(
+        &extension(),
+        &["proto1", "helper", "--setting-version=v1", "--name=join"],
+    );
+    assert_eq!(testing::assert_succeeds(&output), json!(""));
+}
+
+#[test]
+fn unknown_helper() {
+    let output = invoke_helper("split", "v1");
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    assert_eq!(
+        report.message,
+        "No template helper named 'split' is registered for setting version 'v1'"
+    );
+}
+
+#[test]
+fn unknown_version() {
+    let output = invoke_helper("join", "v2");
+    testing::assert_fails(&output, ErrorKind::UnknownVersion);
+}
+
+#[test]
+fn failing_helper() {
+    let output = invoke_helper("broken", "v1");
+    let report = testing::assert_fails(&output, ErrorKind::ModelCallbackFailure);
+    assert_eq!(
+        report.message,
+        "Template helper 'broken' for setting version 'v1' failed"
+    );
+    assert!(report
+        .causes
+        .iter()
+        .any(|cause| cause == "the helper is broken"));
+}
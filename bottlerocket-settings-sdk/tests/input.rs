@@ -0,0 +1,123 @@
+mod common;
+
+use bottlerocket_settings_sdk::{testing, Error, ErrorKind, Output};
+use serde_json::json;
+
+fn invoke_with_stdin(args: &[&str], stdin: impl AsRef<str>) -> Output {
+    testing::invoke_with_stdin(&common::motd_extension(), args, stdin.as_ref())
+}
+
+fn large_value() -> serde_json::Value {
+    json!({ "motd": "x".repeat(2 << 20) })
+}
+
+#[test]
+fn large_value_from_file() {
+    let value = large_value();
+    let path = std::env::temp_dir().join(format!("large-value-{}.json", std::process::id()));
+    std::fs::write(&path, value.to_string()).unwrap();
+
+    let arg = format!("--value=@{}", path.display());
+    let output = invoke_with_stdin(&["proto1", "set", "--setting-version=v1", &arg], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(testing::assert_succeeds(&output), value);
+}
+
+#[test]
+fn large_value_from_stdin() {
+    let value = large_value();
+    let output = invoke_with_stdin(
+        &["proto1", "set", "--setting-version=v1", "--value=-"],
+        value.to_string(),
+    );
+    assert_eq!(testing::assert_succeeds(&output), value);
+}
+
+#[test]
+fn large_value_in_request() {
+    let value = large_value();
+    let request = json!({"command": "set", "setting-version": "v1", "value": value});
+    let output = invoke_with_stdin(&["proto1", "request"], request.to_string());
+    assert_eq!(testing::assert_succeeds(&output), value);
+}
+
+#[test]
+fn large_batch_from_stdin() {
+    let entry = json!({"value": large_value(), "from-version": "v1"});
+    let output = invoke_with_stdin(
+        &[
+            "proto1",
+            "migrate-batch",
+            "--values=-",
+            "--target-version=v1",
+        ],
+        format!("{}\n{}\n", entry, entry),
+    );
+    let results = testing::assert_succeeds(&output);
+    assert_eq!(results.as_array().unwrap().len(), 2);
+    assert_eq!(results[1]["migrated"], large_value());
+}
+
+#[test]
+fn stdin_reused() {
+    let output = invoke_with_stdin(
+        &[
+            "proto1",
+            "set",
+            "--setting-version=v1",
+            "--value=-",
+            "--current-value=-",
+        ],
+        r#"{"motd": "hi"}"#,
+    );
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    assert_eq!(report.message, Error::StdinReused.to_string());
+
+    // Within a request, `-` is an ordinary string rather than a reference to stdin.
+    let request = json!({"command": "set", "setting-version": "v1", "value": "-"});
+    let output = invoke_with_stdin(&["proto1", "request"], request.to_string());
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    assert_ne!(report.message, Error::StdinReused.to_string());
+}
+
+#[test]
+fn each_invocation_reads_its_own_stdin() {
+    let threads: Vec<_> = (0..8)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                for i in 0..25 {
+                    let value = json!({ "motd": format!("{}-{}", thread, i) });
+                    let output = invoke_with_stdin(
+                        &["proto1", "set", "--setting-version=v1", "--value=-"],
+                        value.to_string(),
+                    );
+                    assert_eq!(testing::assert_succeeds(&output), value);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn malformed_input() {
+    let output = invoke_with_stdin(
+        &["proto1", "set", "--setting-version=v1", "--value={bad"],
+        "",
+    );
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    assert_eq!(report.causes.len(), 1);
+
+    let output = invoke_with_stdin(
+        &[
+            "proto1",
+            "set",
+            "--setting-version=v1",
+            "--value=@/nonexistent/value.json",
+        ],
+        "",
+    );
+    testing::assert_fails(&output, ErrorKind::BadInput);
+}
@@ -0,0 +1,303 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+use anyhow::Result;
+use bottlerocket_settings_sdk::error::Result as SdkResult;
+use bottlerocket_settings_sdk::migrate::{
+    BatchMigrationEntry, BatchMigrationResult, GraphMigrator, LinearMigrator, MigrationDirection,
+    MigrationEdge, MigrationRoute, Migrator,
+};
+use bottlerocket_settings_sdk::{
+    testing, BottlerocketSetting, Error, ErrorKind, SettingsExtension, SettingsModel,
+    SettingsPartial,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+// A chain of versions, v1 to v3, with an LTS branch which migrates backward into v2.
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v1", forward = ChainV2, migrate_forward = v1_to_v2)]
+struct ChainV1 {
+    steps: Vec<String>,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(
+    version = "v2",
+    forward = ChainV3,
+    migrate_forward = v2_to_v3,
+    backward = ChainV1,
+    migrate_backward = v2_to_v1
+)]
+struct ChainV2 {
+    steps: Vec<String>,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v3", backward = ChainV2, migrate_backward = v3_to_v2)]
+struct ChainV3 {
+    steps: Vec<String>,
+}
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug)]
+#[settings(version = "v2-lts", backward = ChainV2, migrate_backward = lts_to_v2)]
+struct ChainV2Lts {
+    steps: Vec<String>,
+}
+
+/// Records a migration step in a value's `steps`.
+fn step(mut steps: Vec<String>, step: &str) -> Vec<String> {
+    steps.push(step.to_string());
+    steps
+}
+
+fn v1_to_v2(value: ChainV1) -> Result<ChainV2> {
+    Ok(ChainV2 {
+        steps: step(value.steps, "v1-v2"),
+    })
+}
+
+fn v2_to_v3(value: ChainV2) -> Result<ChainV3> {
+    Ok(ChainV3 {
+        steps: step(value.steps, "v2-v3"),
+    })
+}
+
+fn v2_to_v1(value: ChainV2) -> Result<ChainV1> {
+    Ok(ChainV1 {
+        steps: step(value.steps, "v2-v1"),
+    })
+}
+
+fn v3_to_v2(value: ChainV3) -> Result<ChainV2> {
+    Ok(ChainV2 {
+        steps: step(value.steps, "v3-v2"),
+    })
+}
+
+fn lts_to_v2(value: ChainV2Lts) -> Result<ChainV2> {
+    Ok(ChainV2 {
+        steps: step(value.steps, "lts-v2"),
+    })
+}
+
+fn chain_extension() -> SettingsExtension {
+    SettingsExtension::with_models(vec![
+        BottlerocketSetting::<ChainV1>::model(),
+        BottlerocketSetting::<ChainV2>::model(),
+        BottlerocketSetting::<ChainV3>::model(),
+        BottlerocketSetting::<ChainV2Lts>::model(),
+    ])
+}
+
+fn edge(from: &'static str, to: &'static str, direction: MigrationDirection) -> MigrationEdge {
+    MigrationEdge {
+        from,
+        to,
+        direction,
+    }
+}
+
+/// Finds routes through the migration graph, counting the routes found and marking each value it
+/// migrates.
+#[derive(Debug, Default, Clone)]
+struct MarkingMigrator {
+    routes_found: Arc<AtomicUsize>,
+}
+
+impl Migrator for MarkingMigrator {
+    fn find_route(
+        &self,
+        extension: &SettingsExtension,
+        starting_version: &str,
+        target_version: &str,
+    ) -> SdkResult<MigrationRoute> {
+        self.routes_found.fetch_add(1, Ordering::SeqCst);
+        GraphMigrator.find_route(extension, starting_version, target_version)
+    }
+
+    fn migrate_along(
+        &self,
+        extension: &SettingsExtension,
+        route: &MigrationRoute,
+        starting_value: serde_json::Value,
+    ) -> SdkResult<serde_json::Value> {
+        let mut value = extension.migrate_along(route, starting_value)?;
+        value["motd"] = json!(format!("{} (migrated)", value["motd"].as_str().unwrap()));
+        Ok(value)
+    }
+}
+
+fn extension(migrator: MarkingMigrator) -> SettingsExtension {
+    common::motd_v1_v2_extension().with_migrator(migrator)
+}
+
+fn entry(value: serde_json::Value, from_version: &str) -> BatchMigrationEntry {
+    BatchMigrationEntry {
+        value,
+        from_version: from_version.to_string(),
+    }
+}
+
+#[test]
+fn migrate_uses_migrator() {
+    let extension = extension(MarkingMigrator::default());
+    testing::assert_migrates(
+        &extension,
+        json!({"motd": "hi"}),
+        "v1",
+        "v2",
+        json!({"motd": "hi (migrated)", "author": "unknown"}),
+    );
+}
+
+#[test]
+fn batch_uses_migrator() {
+    let migrator = MarkingMigrator::default();
+    let extension = extension(migrator.clone());
+
+    let results = extension.migrate_batch(
+        vec![
+            entry(json!({"motd": "a"}), "v1"),
+            entry(json!({"motd": "b"}), "v1"),
+            entry(json!({"motd": "c", "author": "me"}), "v2"),
+            entry(json!({"motd": "d"}), "v3"),
+        ],
+        "v2",
+    );
+
+    assert_eq!(
+        results[..3],
+        [
+            BatchMigrationResult::Migrated(json!({"motd": "a (migrated)", "author": "unknown"})),
+            BatchMigrationResult::Migrated(json!({"motd": "b (migrated)", "author": "unknown"})),
+            BatchMigrationResult::Migrated(json!({"motd": "c (migrated)", "author": "me"})),
+        ]
+    );
+    match &results[3] {
+        BatchMigrationResult::Failed(report) => assert_eq!(report.kind, ErrorKind::UnknownVersion),
+        result => panic!("migrating from an unknown version succeeded: {:?}", result),
+    }
+
+    // The route from each registered starting version is found once.
+    assert_eq!(migrator.routes_found.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn linear_forward() {
+    let route = LinearMigrator
+        .find_route(&chain_extension(), "v1", "v3")
+        .unwrap();
+    assert_eq!(
+        route.steps(),
+        [
+            edge("v1", "v2", MigrationDirection::Forward),
+            edge("v2", "v3", MigrationDirection::Forward),
+        ]
+    );
+}
+
+#[test]
+fn linear_backward() {
+    let route = LinearMigrator
+        .find_route(&chain_extension(), "v3", "v1")
+        .unwrap();
+    assert_eq!(
+        route.steps(),
+        [
+            edge("v3", "v2", MigrationDirection::Backward),
+            edge("v2", "v1", MigrationDirection::Backward),
+        ]
+    );
+}
+
+#[test]
+fn linear_no_route() {
+    // Nothing migrates forward into the LTS branch.
+    let error = LinearMigrator
+        .find_route(&chain_extension(), "v1", "v2-lts")
+        .unwrap_err();
+    assert!(matches!(error, Error::MigrationRouteNotFound { .. }));
+}
+
+#[test]
+fn linear_does_not_change_direction() {
+    let extension = chain_extension();
+    let error = LinearMigrator
+        .find_route(&extension, "v2-lts", "v3")
+        .unwrap_err();
+    assert!(matches!(error, Error::MigrationRouteNotFound { .. }));
+
+    // The graph migrates backward into v2, then forward into v3.
+    let route = GraphMigrator
+        .find_route(&extension, "v2-lts", "v3")
+        .unwrap();
+    assert_eq!(route.steps().len(), 2);
+}
+
+#[test]
+fn linear_migrates() {
+    let extension = chain_extension().with_migrator(LinearMigrator);
+    testing::assert_migrates(
+        &extension,
+        json!({"steps": []}),
+        "v1",
+        "v3",
+        json!({"steps": ["v1-v2", "v2-v3"]}),
+    );
+}
+
+#[test]
+fn registered_migration() {
+    let extension = chain_extension().register_migration::<ChainV1, ChainV3>(|value| {
+        Ok(ChainV3 {
+            steps: step(value.steps, "v1-v3"),
+        })
+    });
+    testing::assert_migrates(
+        &extension,
+        json!({"steps": []}),
+        "v1",
+        "v3",
+        json!({"steps": ["v1-v3"]}),
+    );
+}
+
+#[test]
+fn registered_migration_failure() {
+    let extension = chain_extension()
+        .register_migration::<ChainV1, ChainV3>(|_| Err(anyhow::anyhow!("v3 is unavailable")));
+
+    let route = GraphMigrator.find_route(&extension, "v1", "v3").unwrap();
+    assert_eq!(
+        route.steps(),
+        [edge("v1", "v3", MigrationDirection::Registered)]
+    );
+    let error = extension
+        .migrate_along(&route, json!({"steps": []}))
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        Error::MigrationFailed { from_version, to_version, .. }
+            if from_version == "v1" && to_version == "v3"
+    ));
+
+    let output = testing::invoke(
+        &extension,
+        &[
+            "proto1",
+            "migrate",
+            r#"--value={"steps": []}"#,
+            "--from-version=v1",
+            "--target-version=v3",
+        ],
+    );
+    let report = testing::assert_fails(&output, ErrorKind::MigrationFailure);
+    assert!(report
+        .causes
+        .iter()
+        .any(|cause| cause == "v3 is unavailable"));
+}
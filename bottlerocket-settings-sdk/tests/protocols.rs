@@ -0,0 +1,146 @@
+mod common;
+
+use bottlerocket_settings_sdk::{testing, Error, ErrorKind, SettingsExtension};
+use common::motd_v1_v2_extension as extension;
+use serde_json::json;
+
+fn run(
+    extension: &SettingsExtension,
+    protocol: &str,
+    operation: &str,
+    args: &[&str],
+) -> bottlerocket_settings_sdk::Output {
+    let args: Vec<&str> = [protocol, operation]
+        .into_iter()
+        .chain(args.iter().copied())
+        .collect();
+    testing::invoke(extension, &args)
+}
+
+/// Runs a command with both protocols, checking that proto2 wraps the result of proto1.
+fn assert_same_result(extension: &SettingsExtension, operation: &str, args: &[&str]) {
+    let proto1 = testing::assert_succeeds(&run(extension, "proto1", operation, args));
+    let proto2 = testing::assert_succeeds(&run(extension, "proto2", operation, args));
+    assert_eq!(
+        proto2,
+        json!({"protocol": "proto2", "operation": operation, "result": proto1})
+    );
+}
+
+/// Runs a command with both protocols, checking that both fail with the same error report.
+fn assert_same_failure(
+    extension: &SettingsExtension,
+    operation: &str,
+    args: &[&str],
+    kind: ErrorKind,
+) {
+    let proto1 = run(extension, "proto1", operation, args);
+    let proto2 = run(extension, "proto2", operation, args);
+    assert_eq!(
+        testing::assert_fails(&proto1, kind),
+        testing::assert_fails(&proto2, kind)
+    );
+}
+
+#[test]
+fn protocols_share_operations() {
+    let extension = extension();
+    let v2 = r#"--value={"motd": "hi", "author": "me"}"#;
+
+    assert_same_result(&extension, "set", &["--setting-version=v2", v2]);
+    assert_same_result(
+        &extension,
+        "generate",
+        &[
+            "--setting-version=v1",
+            r#"--existing-partial={"motd": "hi"}"#,
+        ],
+    );
+    assert_same_result(&extension, "validate", &["--setting-version=v2", v2]);
+    assert_same_result(
+        &extension,
+        "migrate",
+        &[v2, "--from-version=v2", "--target-version=v1"],
+    );
+    assert_same_result(
+        &extension,
+        "migrate-batch",
+        &[
+            r#"--values=[{"value": {"motd": "hi"}, "from-version": "v1"}]"#,
+            "--target-version=v2",
+        ],
+    );
+}
+
+#[test]
+fn protocols_share_failures() {
+    let extension = extension();
+    assert_same_failure(
+        &extension,
+        "set",
+        &["--setting-version=v3", r#"--value={"motd": "hi"}"#],
+        ErrorKind::UnknownVersion,
+    );
+    assert_same_failure(
+        &extension,
+        "set",
+        &[
+            "--setting-version=v2",
+            r#"--value={"motd": "", "author": "me"}"#,
+        ],
+        ErrorKind::ValidationFailure,
+    );
+    assert_same_failure(
+        &extension,
+        "migrate",
+        &[
+            r#"--value={"motd": 1}"#,
+            "--from-version=v1",
+            "--target-version=v2",
+        ],
+        ErrorKind::MigrationFailure,
+    );
+}
+
+fn negotiate(accept: &[&str]) -> std::result::Result<&'static str, Error> {
+    let accept: Vec<String> = accept.iter().map(|p| p.to_string()).collect();
+    let negotiation = extension().negotiate_protocol(&accept)?;
+    assert_eq!(negotiation.supported, ["proto1", "proto2"]);
+    Ok(negotiation.selected)
+}
+
+#[test]
+fn negotiate_preferred() {
+    assert_eq!(negotiate(&["proto1", "proto2"]).unwrap(), "proto1");
+    assert_eq!(negotiate(&["proto2", "proto1"]).unwrap(), "proto2");
+}
+
+#[test]
+fn negotiate_skips_unknown() {
+    assert_eq!(negotiate(&["proto9", "proto1"]).unwrap(), "proto1");
+    assert!(matches!(
+        negotiate(&["proto9", "proto0"]),
+        Err(Error::NoCommonProtocol { accepted }) if accepted == ["proto9", "proto0"]
+    ));
+}
+
+#[test]
+fn negotiate_empty_selects_newest() {
+    assert_eq!(negotiate(&[]).unwrap(), "proto2");
+}
+
+#[test]
+fn negotiate_command() {
+    let extension = extension();
+    let output = testing::invoke(&extension, &["negotiate", "--accept=proto9,proto1"]);
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({"supported": ["proto1", "proto2"], "selected": "proto1"})
+    );
+
+    let output = testing::invoke(&extension, &["negotiate"]);
+    assert_eq!(testing::assert_succeeds(&output)["selected"], "proto2");
+
+    let output = testing::invoke(&extension, &["negotiate", "--accept=proto9"]);
+    testing::assert_fails(&output, ErrorKind::BadInput);
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "handlebars")]
+
+mod common;
+
+use bottlerocket_settings_sdk::{testing, Error, ErrorKind, SettingsExtension};
+use common::Motd;
+use serde_json::json;
+
+fn extension(template: &str) -> SettingsExtension {
+    common::motd_extension().register_template::<Motd>(template)
+}
+
+#[test]
+fn renders_value_as_parsed_by_model() {
+    let value = json!({"motd": "hi", "extra": "!"});
+    assert_eq!(
+        extension("{{motd}}").render_template("v1", &value).unwrap(),
+        "hi"
+    );
+
+    // Fields which the model does not have are dropped before rendering.
+    let error = extension("{{motd}}{{extra}}")
+        .render_template("v1", &value)
+        .unwrap_err();
+    assert!(matches!(error, Error::RenderTemplate { .. }));
+}
+
+#[test]
+fn malformed_value_is_bad_input() {
+    let extension = extension("{{motd}}");
+    let error = extension
+        .render_template("v1", &json!({"motd": 7}))
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::BadInput);
+
+    let output = testing::invoke(
+        &extension,
+        &[
+            "proto1",
+            "render",
+            "--setting-version=v1",
+            r#"--value={"extra": "!"}"#,
+        ],
+    );
+    testing::assert_fails(&output, ErrorKind::BadInput);
+}
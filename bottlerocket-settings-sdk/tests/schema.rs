@@ -0,0 +1,57 @@
+#![cfg(feature = "schemars")]
+
+mod common;
+
+use bottlerocket_settings_sdk::schema::{json_schema_for, schemars};
+use bottlerocket_settings_sdk::{
+    testing, BottlerocketSetting, Error, ErrorKind, SettingsExtension, SettingsModel,
+    SettingsPartial,
+};
+use common::Motd;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(SettingsModel, SettingsPartial, JsonSchema, Serialize, Deserialize, Debug)]
+#[settings(version = "v2", schema)]
+#[schemars(crate = "bottlerocket_settings_sdk::schema::schemars")]
+struct Banner {
+    /// The text of the banner
+    text: String,
+}
+
+fn extension() -> SettingsExtension {
+    SettingsExtension::with_models(vec![
+        BottlerocketSetting::<Motd>::model(),
+        BottlerocketSetting::<Banner>::model(),
+    ])
+}
+
+#[test]
+fn published_schema() {
+    let schema = <Banner as SettingsModel>::json_schema().unwrap();
+    assert_eq!(schema, json_schema_for::<Banner>());
+    assert_eq!(schema["properties"]["text"]["type"], "string");
+
+    let output = testing::invoke(&extension(), &["proto1", "schema", "--setting-version=v2"]);
+    assert_eq!(testing::assert_succeeds(&output), schema);
+}
+
+#[test]
+fn schema_not_available() {
+    let output = testing::invoke(&extension(), &["proto1", "schema", "--setting-version=v1"]);
+    let report = testing::assert_fails(&output, ErrorKind::BadInput);
+    let expected = Error::SchemaNotAvailable {
+        version: "v1".to_string(),
+    };
+    assert_eq!(report.message, expected.to_string());
+}
+
+#[test]
+fn all_schemas() {
+    let output = testing::invoke(&extension(), &["proto1", "schema"]);
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({"v1": null, "v2": json_schema_for::<Banner>()})
+    );
+}
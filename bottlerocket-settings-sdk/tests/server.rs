@@ -0,0 +1,122 @@
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bottlerocket_settings_sdk::Error;
+use serde_json::json;
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}.sock", name, std::process::id()))
+}
+
+fn connect(path: &Path) -> UnixStream {
+    for _ in 0..500 {
+        if let Ok(stream) = UnixStream::connect(path) {
+            return stream;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("server did not listen on {}", path.display());
+}
+
+fn request(id: u64, method: &str) -> String {
+    let params = json!({"setting-version": "v1", "value": {"motd": "hi"}});
+    format!(
+        "{}\n",
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    )
+}
+
+fn read_response(reader: &mut impl BufRead) -> serde_json::Value {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+#[test]
+fn survives_failed_connections() {
+    let path = socket_path("survives-failed-connections");
+    let _ = std::fs::remove_file(&path);
+
+    let client_path = path.clone();
+    let clients = std::thread::spawn(move || {
+        // A line which is not UTF-8 is answered as a malformed request.
+        let mut stream = connect(&client_path);
+        stream.write_all(b"\xff\xfe\n").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        assert_eq!(read_response(&mut reader)["error"]["code"], -32700);
+
+        // While the server is busy with the first client, a second client sends a request and
+        // hangs up, so answering it fails with a broken pipe.
+        let mut hung_up = connect(&client_path);
+        hung_up.write_all(request(1, "set").as_bytes()).unwrap();
+        drop(hung_up);
+
+        stream.write_all(request(2, "validate").as_bytes()).unwrap();
+        assert_eq!(read_response(&mut reader)["result"]["valid"], true);
+        drop((stream, reader));
+
+        let mut stream = connect(&client_path);
+        stream.write_all(request(3, "validate").as_bytes()).unwrap();
+        stream.write_all(request(4, "shutdown").as_bytes()).unwrap();
+        let mut reader = BufReader::new(stream);
+        assert_eq!(read_response(&mut reader)["result"]["valid"], true);
+        assert_eq!(read_response(&mut reader)["id"], 4);
+    });
+
+    common::motd_extension().serve_unix_socket(&path).unwrap();
+    clients.join().unwrap();
+    assert!(!path.exists());
+}
+
+fn serve_and_shut_down(path: &Path) {
+    let client_path = path.to_path_buf();
+    let client = std::thread::spawn(move || {
+        let mut stream = connect(&client_path);
+        stream.write_all(request(1, "shutdown").as_bytes()).unwrap();
+        assert_eq!(read_response(&mut BufReader::new(stream))["id"], 1);
+    });
+    common::motd_extension().serve_unix_socket(path).unwrap();
+    client.join().unwrap();
+}
+
+#[test]
+fn replaces_stale_socket() {
+    let path = socket_path("replaces-stale-socket");
+    let _ = std::fs::remove_file(&path);
+
+    // A server which is killed leaves its socket behind, with nothing listening on it.
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    serve_and_shut_down(&path);
+    assert!(!path.exists());
+}
+
+#[test]
+fn keeps_live_socket() {
+    let path = socket_path("keeps-live-socket");
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).unwrap();
+    let result = common::motd_extension().serve_unix_socket(&path);
+    assert!(matches!(result, Err(Error::BindSocket { .. })));
+    assert!(path.exists());
+
+    drop(listener);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn keeps_other_files() {
+    let path = socket_path("keeps-other-files");
+    std::fs::write(&path, "not a socket").unwrap();
+
+    let result = common::motd_extension().serve_unix_socket(&path);
+    assert!(matches!(result, Err(Error::BindSocket { .. })));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use bottlerocket_settings_sdk::{
+    testing, BottlerocketSetting, ErrorKind, SettingsExtension, SettingsModel, SettingsPartial,
+    ValidationReport,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(SettingsModel, SettingsPartial, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[settings(
+    version = "v1",
+    validate = validate_motd,
+    dependent_settings = Hostname,
+    validated_setting(name = "hostname", version = "v1")
+)]
+struct Motd {
+    #[settings(validate(min_len = 1))]
+    motd: String,
+}
+
+fn validate_motd(value: Motd, hostname: Option<Hostname>) -> Result<ValidationReport> {
+    let hostname = hostname.expect("validate is only called with the validated settings");
+    let mut report = ValidationReport::new();
+    if value.motd.contains(&hostname.hostname) {
+        report.error("motd", "must not mention the hostname");
+    }
+    Ok(report)
+}
+
+#[derive(Deserialize, Debug)]
+struct Hostname {
+    hostname: String,
+}
+
+fn extension() -> SettingsExtension {
+    SettingsExtension::with_models(vec![BottlerocketSetting::<Motd>::model()])
+}
+
+fn set(
+    motd: &str,
+    validated_settings: Option<serde_json::Value>,
+) -> bottlerocket_settings_sdk::Output {
+    let mut args = vec![
+        "proto1".to_string(),
+        "set".to_string(),
+        "--setting-version=v1".to_string(),
+        format!("--value={}", json!({ "motd": motd })),
+    ];
+    if let Some(validated_settings) = validated_settings {
+        args.push(format!("--validated-settings={}", validated_settings));
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    testing::invoke(&extension(), &args)
+}
+
+#[test]
+fn without_validated_settings_checks_fields() {
+    let output = set("welcome to host-1", None);
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({"motd": "welcome to host-1"})
+    );
+
+    let report = testing::assert_fails(&set("", None), ErrorKind::ValidationFailure);
+    let validation = report.validation.unwrap();
+    assert_eq!(validation.errors().next().unwrap().field, "motd");
+}
+
+#[test]
+fn with_validated_settings_checks_dependencies() {
+    let hostname = json!({"hostname": "host-1"});
+    let output = set("welcome", Some(hostname.clone()));
+    assert_eq!(
+        testing::assert_succeeds(&output),
+        json!({"motd": "welcome"})
+    );
+
+    let output = set("welcome to host-1", Some(hostname.clone()));
+    testing::assert_fails(&output, ErrorKind::ValidationFailure);
+
+    let output = set("", Some(hostname));
+    let report = testing::assert_fails(&output, ErrorKind::ValidationFailure);
+    assert_eq!(report.validation.unwrap().errors().count(), 1);
+}
+
+#[test]
+fn validate_requires_validated_settings() {
+    let extension = extension();
+    let args = [
+        "proto1",
+        "validate",
+        "--setting-version=v1",
+        r#"--value={"motd": "welcome"}"#,
+    ];
+    let output = testing::invoke(&extension, &args);
+    testing::assert_fails(&output, ErrorKind::BadInput);
+}
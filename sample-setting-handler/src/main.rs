@@ -1,14 +1,181 @@
 use anyhow::{Context, Result};
-use bottlerocket_settings_sdk::BottlerocketSetting;
+use bottlerocket_settings_sdk::config::ExtensionConfig;
+use bottlerocket_settings_sdk::{BottlerocketSetting, SettingsExtension};
 
 pub(crate) mod v1;
 pub(crate) mod v2;
 
 fn main() -> Result<()> {
-    bottlerocket_settings_sdk::SettingsExtension::with_models(vec![
+    extension()?
+        .run_extension()
+        .context("Settings extension encountered an error.")
+}
+
+fn extension() -> Result<SettingsExtension> {
+    let extension = SettingsExtension::try_with_models(vec![
         BottlerocketSetting::<v1::MotdV1>::model(),
         BottlerocketSetting::<v2::MotdV2>::model(),
     ])
-    .run_extension()
-    .context("Settings extension encountered an error.")
+    .context("Settings extension models are inconsistent.")?
+    .register_helper::<v1::MotdV1>("sample-helper", v1::sample_helper)
+    .register_template::<v1::MotdV1>(include_str!("../templates/motd-v1.hbs"))
+    .register_template::<v2::MotdV2>(include_str!("../templates/motd-v2.hbs"));
+
+    let config = ExtensionConfig::from_toml(include_str!("../motd.toml"))
+        .context("Failed to parse settings extension config.")?;
+    extension
+        .check_config(&config)
+        .context("Settings extension config does not match its models.")?;
+
+    Ok(extension)
+}
+
+#[cfg(test)]
+mod test {
+    use bottlerocket_settings_sdk::{testing, ErrorKind, GenerateResult};
+    use serde_json::json;
+
+    use super::extension;
+    use crate::v1::{MotdV1, MotdV1Partial};
+    use crate::v2::{MotdV2, MotdV2Partial};
+
+    fn motd_v1(motd: &str) -> MotdV1 {
+        MotdV1 {
+            motd: motd.to_string(),
+        }
+    }
+
+    fn motd_v2(motd: &str, person: &str) -> MotdV2 {
+        MotdV2 {
+            motd: motd.to_string(),
+            person: person.to_string(),
+        }
+    }
+
+    #[test]
+    fn set() {
+        let extension = extension().unwrap();
+        testing::assert_set(
+            &extension,
+            "v1",
+            None::<MotdV1>,
+            motd_v1("hi"),
+            motd_v1("hi"),
+        );
+        testing::assert_set(
+            &extension,
+            "v2",
+            motd_v2("old", "me"),
+            motd_v2("new", "you"),
+            motd_v2("new", "you"),
+        );
+
+        let too_long = motd_v2(&"x".repeat(256), "me");
+        let output = testing::invoke(
+            &extension,
+            &[
+                "proto1",
+                "set",
+                "--setting-version=v2",
+                &format!("--value={}", json!(too_long)),
+            ],
+        );
+        let report = testing::assert_fails(&output, ErrorKind::ValidationFailure);
+        assert_eq!(
+            report.validation.unwrap().errors().next().unwrap().field,
+            "motd"
+        );
+    }
+
+    #[test]
+    fn generate() {
+        let extension = extension().unwrap();
+        testing::assert_generates(
+            &extension,
+            "v1",
+            None::<MotdV1Partial>,
+            GenerateResult::<MotdV1Partial, _>::Complete(Some(motd_v1(""))),
+        );
+        testing::assert_generates(
+            &extension,
+            "v2",
+            MotdV2Partial {
+                motd: Some("hi".to_string()),
+                person: None,
+            },
+            GenerateResult::<MotdV2Partial, _>::Complete(Some(motd_v2("hi", ""))),
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let extension = extension().unwrap();
+        let report = testing::assert_valid(&extension, "v2", motd_v2("hi", "me"));
+        assert!(report.issues().is_empty());
+
+        let report = testing::assert_invalid(&extension, "v2", motd_v2(&"x".repeat(256), "me"));
+        assert_eq!(report.errors().count(), 1);
+    }
+
+    #[test]
+    fn migrate() {
+        let extension = extension().unwrap();
+        testing::assert_migrates(&extension, motd_v1("hi"), "v1", "v2", motd_v2("hi", "Sean"));
+        testing::assert_migrates(&extension, motd_v2("hi", "me"), "v2", "v1", motd_v1("hi"));
+    }
+
+    #[test]
+    fn render() {
+        let extension = extension().unwrap();
+        let output = testing::invoke(
+            &extension,
+            &[
+                "proto1",
+                "render",
+                "--setting-version=v2",
+                r#"--value={"motd": "hi", "person": "me"}"#,
+            ],
+        );
+        assert_eq!(testing::assert_succeeds(&output), "hi\n\n    -- me\n");
+    }
+
+    #[test]
+    fn stdin() {
+        let extension = extension().unwrap();
+        let output = testing::invoke_with_stdin(
+            &extension,
+            &["proto1", "set", "--setting-version=v1", "--value=-"],
+            r#"{"motd": "hi"}"#,
+        );
+        assert_eq!(testing::assert_succeeds(&output), json!(motd_v1("hi")));
+
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": "validate",
+            "params": {"setting-version": "v1", "value": {"motd": "hi"}}});
+        let output = testing::invoke_with_stdin(&extension, &["serve"], &request.to_string());
+        assert_eq!(testing::assert_succeeds(&output)["result"]["valid"], true);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let extension = extension().unwrap();
+        let output = testing::invoke(&extension, &["proto1", "set", "--setting-version=v1"]);
+        let report = testing::assert_fails(&output, ErrorKind::BadInput);
+        assert!(report.message.contains("--value"), "{}", report.message);
+
+        let output = testing::invoke(
+            &extension,
+            &["proto1", "set", "--setting-version=v1", "--value={"],
+        );
+        testing::assert_fails(&output, ErrorKind::BadInput);
+
+        let output = testing::invoke(
+            &extension,
+            &["proto1", "set", "--setting-version=v1", "--value=-"],
+        );
+        testing::assert_fails(&output, ErrorKind::BadInput);
+
+        let output = testing::invoke(&extension, &["--help"]);
+        assert!(output.success());
+        assert!(output.stdout.contains("Usage:"), "{}", output.stdout);
+    }
 }
@@ -1,52 +1,67 @@
 use anyhow::Result;
-use bottlerocket_settings_sdk::{GenerateResult, NoMigration, SettingsModel};
+use bottlerocket_settings_sdk::template::HelperArgs;
+use bottlerocket_settings_sdk::{
+    GenerateResult, NoDependentSettings, Partial, SettingsModel, SettingsPartial,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::v2;
 
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[derive(
+    SettingsModel,
+    SettingsPartial,
+    JsonSchema,
+    Serialize,
+    Deserialize,
+    Debug,
+    Default,
+    PartialEq,
+    Clone,
+)]
+#[settings(
+    version = "v1",
+    forward = v2::MotdV2,
+    migrate_forward = migrate_to_v2,
+    generate = generate,
+    schema
+)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MotdV1 {
     pub(crate) motd: String,
 }
 
-impl SettingsModel for MotdV1 {
-    type PartialType = MotdV1Partial;
-    type ForwardMigrationTarget = v2::MotdV2;
-    type BackwardMigrationTarget = NoMigration;
-
-    fn get_version() -> &'static str {
-        "v1"
-    }
-
-    fn set(_current_value: Option<Self>, target: Self) -> Result<Self> {
-        Ok(target)
-    }
-
-    fn generate(
-        _: Option<Self::PartialType>,
-        _: Option<serde_json::Value>,
-    ) -> Result<GenerateResult<Self::PartialType, Self>> {
-        Ok(GenerateResult::Complete(Some(MotdV1::default())))
-    }
-
-    fn validate(_value: Self, _validated_settings: Option<serde_json::Value>) -> Result<bool> {
-        Ok(true)
-    }
-
-    fn migrate_forward(self) -> Result<Self::ForwardMigrationTarget> {
-        Ok(v2::MotdV2 {
-            motd: self.motd,
-            person: "Sean".to_string(),
-        })
-    }
+fn generate(
+    existing_partial: Option<MotdV1Partial>,
+    _: Option<NoDependentSettings>,
+) -> Result<GenerateResult<MotdV1Partial, MotdV1>> {
+    let generated = existing_partial
+        .unwrap_or_default()
+        .merge_into(MotdV1::default());
+    Ok(GenerateResult::Complete(Some(generated)))
+}
 
-    fn migrate_backward(self) -> Result<Self::BackwardMigrationTarget> {
-        NoMigration::no_defined_migration()
-    }
+fn migrate_to_v2(motd: MotdV1) -> Result<v2::MotdV2> {
+    Ok(v2::MotdV2 {
+        motd: motd.motd,
+        person: "Sean".to_string(),
+    })
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
-pub(crate) struct MotdV1Partial {
-    pub motd: Option<String>,
+/// Joins its params with the `separator` hash argument, or with spaces if none is given.
+pub(crate) fn sample_helper(args: &HelperArgs) -> Result<String> {
+    let separator = match args.hash.get("separator") {
+        Some(serde_json::Value::String(separator)) => separator.as_str(),
+        Some(other) => anyhow::bail!("separator must be a string, not {}", other),
+        None => " ",
+    };
+    let params: Vec<String> = args
+        .params
+        .iter()
+        .map(|param| match param {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+    Ok(params.join(separator))
 }